@@ -0,0 +1,72 @@
+use crate::config::TimeConfig;
+use chrono::{
+    DateTime, Days, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+// Decides which practice day a moment falls on: dates are taken in the configured time
+// zone (the system one by default) and a day only ends at `day_starts_at` the next morning.
+#[derive(Debug, Clone, Copy)]
+pub struct Calendar {
+    timezone: Option<Tz>, // None for the system time zone
+    day_starts_at: u32,
+}
+
+impl From<&TimeConfig> for Calendar {
+    fn from(config: &TimeConfig) -> Self {
+        Calendar::new(config.timezone, config.day_starts_at)
+    }
+}
+
+impl Calendar {
+    pub fn new(timezone: Option<Tz>, day_starts_at: u32) -> Self {
+        Calendar {
+            timezone,
+            day_starts_at,
+        }
+    }
+
+    pub fn day_of(&self, moment: DateTime<Utc>) -> NaiveDate {
+        let local = match self.timezone {
+            Some(tz) => moment.with_timezone(&tz).naive_local(),
+            None => moment.with_timezone(&chrono::Local).naive_local(),
+        };
+        (local - TimeDelta::hours(self.day_starts_at as i64)).date()
+    }
+
+    pub fn today(&self, now: DateTime<Utc>) -> NaiveDate {
+        self.day_of(now)
+    }
+
+    // The moment the given practice day begins
+    pub fn start_of_day(&self, day: NaiveDate) -> DateTime<Utc> {
+        let start = day.and_time(NaiveTime::MIN) + TimeDelta::hours(self.day_starts_at as i64);
+        match self.timezone {
+            Some(tz) => to_utc(&tz, start),
+            None => to_utc(&chrono::Local, start),
+        }
+    }
+
+    // Half-open range covering the given practice day
+    pub fn day_range(&self, day: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            self.start_of_day(day),
+            self.start_of_day(day + Days::new(1)),
+        )
+    }
+
+    pub fn format_date(&self, moment: DateTime<Utc>) -> String {
+        self.day_of(moment).format("%Y-%m-%d").to_string()
+    }
+}
+
+// Local wall clock time to UTC. Ambiguous times (clocks going back) take the earlier
+// moment and skipped ones (clocks going forward) move past the gap.
+fn to_utc<Z: TimeZone>(tz: &Z, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(moment) | LocalResult::Ambiguous(moment, _) => {
+            moment.with_timezone(&Utc)
+        }
+        LocalResult::None => to_utc(tz, local + TimeDelta::hours(1)),
+    }
+}
@@ -0,0 +1,171 @@
+use crate::db::models::Difficulty;
+use serde::Deserialize;
+use std::{str::FromStr, sync::OnceLock};
+
+// NeetCode 150 (which includes the Blind 75), embedded so seeding and autocomplete work offline
+const NEETCODE_CSV: &str = include_str!("neetcode.csv");
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ProblemList {
+    NeetCode150,
+    Blind75,
+}
+
+impl ProblemList {
+    fn key(&self) -> &'static str {
+        match self {
+            ProblemList::NeetCode150 => "neetcode150",
+            ProblemList::Blind75 => "blind75",
+        }
+    }
+}
+
+impl FromStr for ProblemList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "neetcode150" => Ok(ProblemList::NeetCode150),
+            "blind75" => Ok(ProblemList::Blind75),
+            _ => Err(format!(
+                "Unknown problem list: {s} (expected neetcode150 or blind75)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CatalogEntry {
+    pub lc_number: u32,
+    pub problem_name: String,
+    pub problem_type: String,
+    pub difficulty: Difficulty,
+    lists: String, // space separated list keys
+}
+
+impl CatalogEntry {
+    pub fn in_list(&self, list: ProblemList) -> bool {
+        self.lists.split_whitespace().any(|key| key == list.key())
+    }
+}
+
+pub fn catalog() -> &'static [CatalogEntry] {
+    static CATALOG: OnceLock<Vec<CatalogEntry>> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        csv::Reader::from_reader(NEETCODE_CSV.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .expect("Embedded catalog is valid CSV")
+    })
+}
+
+pub fn lookup(lc_number: u32) -> Option<&'static CatalogEntry> {
+    catalog().iter().find(|entry| entry.lc_number == lc_number)
+}
+
+// Problems whose number starts with the typed digits, lowest number first
+pub fn search_by_number(prefix: &str) -> Vec<&'static CatalogEntry> {
+    let prefix = prefix.trim();
+    if prefix.is_empty() {
+        return vec![];
+    }
+
+    let mut matches: Vec<&CatalogEntry> = catalog()
+        .iter()
+        .filter(|entry| entry.lc_number.to_string().starts_with(prefix))
+        .collect();
+    matches.sort_by_key(|entry| entry.lc_number);
+    matches
+}
+
+// Case-insensitive name search, names starting with the query first
+pub fn search_by_name(query: &str) -> Vec<&'static CatalogEntry> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let mut matches: Vec<(bool, &CatalogEntry)> = catalog()
+        .iter()
+        .filter_map(|entry| {
+            let name = entry.problem_name.to_lowercase();
+            if name.starts_with(&query) {
+                Some((true, entry))
+            } else if name.contains(&query) {
+                Some((false, entry))
+            } else {
+                None
+            }
+        })
+        .collect();
+    matches.sort_by_key(|(prefix, entry)| (!prefix, entry.problem_name.as_str()));
+    matches.into_iter().map(|(_, entry)| entry).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(entries: &[&CatalogEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| entry.problem_name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn the_catalog_holds_the_neetcode_150_with_the_blind_75_marked() {
+        assert_eq!(catalog().len(), 150);
+        let blind75 = catalog()
+            .iter()
+            .filter(|entry| entry.in_list(ProblemList::Blind75))
+            .count();
+        assert_eq!(blind75, 75);
+        assert!(catalog()
+            .iter()
+            .all(|entry| entry.in_list(ProblemList::NeetCode150)));
+    }
+
+    #[test]
+    fn lookup_finds_problems_by_number() {
+        let entry = lookup(42).unwrap();
+        assert_eq!(entry.problem_name, "Trapping Rain Water");
+        assert_eq!(entry.problem_type, "Two Pointers");
+        assert_eq!(entry.difficulty, Difficulty::Hard);
+        assert!(!entry.in_list(ProblemList::Blind75));
+        assert!(lookup(9999).is_none());
+    }
+
+    #[test]
+    fn number_search_matches_prefixes_lowest_first() {
+        let numbers: Vec<u32> = search_by_number(" 21 ")
+            .iter()
+            .map(|entry| entry.lc_number)
+            .collect();
+        assert_eq!(numbers, vec![21, 210, 211, 212, 213, 215, 217]);
+        assert!(search_by_number("").is_empty());
+        assert!(search_by_number("9999").is_empty());
+    }
+
+    #[test]
+    fn name_search_puts_prefix_matches_first() {
+        assert_eq!(
+            names(&search_by_name("WORD")),
+            vec![
+                "Word Break",
+                "Word Ladder",
+                "Word Search",
+                "Word Search II",
+                "Design Add and Search Words Data Structure",
+            ]
+        );
+        assert!(search_by_name("  ").is_empty());
+        assert!(search_by_name("no such problem").is_empty());
+    }
+
+    #[test]
+    fn problem_lists_parse_case_insensitively() {
+        assert_eq!("Blind75".parse::<ProblemList>(), Ok(ProblemList::Blind75));
+        assert!("grind75".parse::<ProblemList>().is_err());
+    }
+}
@@ -0,0 +1,659 @@
+use clap::{Parser, Subcommand};
+use rusty_lcurve::{
+    calendar::Calendar,
+    catalog::{self, catalog, ProblemList},
+    clock::Clock,
+    config::Config,
+    db::{
+        db::Rows,
+        models::{Difficulty, LCProblem, Rating, Solution},
+        store::ProblemStore,
+    },
+    error::{Context, Error, Result},
+    exchange::{
+        export::export,
+        import::{import, DuplicateStrategy, ImportAction},
+        reviews_csv_path, Format,
+    },
+    heatmap::Heatmap,
+    language::Language,
+    scheduler::{
+        fsrs::{self, DEFAULT_WEIGHTS},
+        Scheduler, SchedulerKind,
+    },
+    stats::{average_interval, category_stats, difficulty_stats, CategoryStats},
+    streak::compute_streaks,
+    utils::{get_todays_problems, search_problems},
+    validation::type_validator,
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Keeps `done --minutes` in seconds well clear of overflowing u32
+const MAX_MINUTES: i64 = 24 * 60;
+
+#[derive(Parser)]
+#[command(
+    name = "rusty_lcurve",
+    version,
+    about = "Spaced repetition for LeetCode practice"
+)]
+pub struct Cli {
+    /// Path to the database file
+    #[arg(long, global = true, env = "RUSTY_LCURVE_DB")]
+    pub db: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Launch the terminal UI (the default when no command is given)
+    Tui,
+    /// Try the terminal UI on the Blind 75 kept in memory, nothing is saved
+    Demo,
+    /// Add a new problem
+    Add {
+        lc_number: u32,
+        /// Problem name, e.g. "Two Sum"
+        name: String,
+        /// One of the configured categories, e.g. "Arrays & Hashing"
+        #[arg(short, long)]
+        category: String,
+        /// easy, medium or hard; looked up in the catalog when omitted
+        #[arg(short, long)]
+        difficulty: Option<Difficulty>,
+    },
+    /// List every problem in the database
+    List {
+        /// Only list problems of this difficulty
+        #[arg(short, long)]
+        difficulty: Option<Difficulty>,
+    },
+    /// Show the problems due today
+    Today {
+        /// Only show today's problems of this difficulty, scheduling still covers them all
+        #[arg(short, long)]
+        difficulty: Option<Difficulty>,
+    },
+    /// Mark a problem as practiced
+    Done {
+        lc_number: u32,
+        /// How the attempt went: again, hard, good or easy
+        #[arg(short, long, default_value = "good")]
+        rating: Rating,
+        /// Minutes spent on the attempt, at most a day
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(0..=MAX_MINUTES))]
+        minutes: Option<u32>,
+        /// Notes for this attempt
+        #[arg(short, long)]
+        notes: Option<String>,
+    },
+    /// Show a problem's notes, or replace them
+    Notes {
+        lc_number: u32,
+        /// New notes; an empty string clears them
+        text: Option<String>,
+    },
+    /// Save a solution file as the newest version of a problem's solution
+    Solution {
+        lc_number: u32,
+        /// Source file to read
+        path: PathBuf,
+        /// e.g. rust or python, inferred from the file extension when omitted
+        #[arg(short, long)]
+        language: Option<String>,
+    },
+    /// List the saved versions of a problem's solution, or print one
+    Solutions {
+        lc_number: u32,
+        /// Print this version's code
+        #[arg(short, long)]
+        version: Option<u32>,
+    },
+    /// Find problems by name or by what their notes say
+    Search { query: String },
+    /// Summarize problems and practice history
+    Stats,
+    /// Fit the FSRS weights to the review history now instead of waiting for it to grow
+    Optimize,
+    /// Write a calendar heatmap of the past year's reviews as SVG
+    Heatmap {
+        /// Output file
+        path: PathBuf,
+    },
+    /// Add problems from the built-in NeetCode 150 / Blind 75 catalog
+    Seed {
+        /// neetcode150 or blind75
+        #[arg(short, long, default_value = "neetcode150")]
+        list: ProblemList,
+        /// Print what would be added without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export problems and review history to JSON or CSV
+    Export {
+        /// Output file. For CSV the reviews are written to <name>.reviews.csv next to it
+        path: PathBuf,
+        /// json or csv, inferred from the file extension when omitted
+        #[arg(short, long)]
+        format: Option<Format>,
+    },
+    /// Import problems (and review history) from JSON or CSV
+    Import {
+        /// Input file. For CSV, reviews are read from <name>.reviews.csv next to it if present
+        path: PathBuf,
+        /// json or csv, inferred from the file extension when omitted
+        #[arg(short, long)]
+        format: Option<Format>,
+        /// What to do with problems whose number or name already exists: skip, overwrite or merge
+        #[arg(long, default_value = "skip")]
+        on_duplicate: DuplicateStrategy,
+        /// Print what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub fn run(
+    command: Command,
+    store: &dyn ProblemStore,
+    scheduler: &dyn Scheduler,
+    config: &Config,
+    clock: &dyn Clock,
+) -> Result<()> {
+    let calendar = Calendar::from(&config.time);
+    match command {
+        Command::Tui | Command::Demo => unreachable!("the TUI is launched from main"),
+        Command::Add {
+            lc_number,
+            name,
+            category,
+            difficulty,
+        } => add(
+            store, config, lc_number, &name, &category, difficulty, clock,
+        ),
+        Command::List { difficulty } => {
+            print_problems(
+                &with_difficulty(readable(store.problems()?), difficulty),
+                &calendar,
+            );
+            Ok(())
+        }
+        Command::Today { difficulty } => {
+            let todays_problems = get_todays_problems(
+                &readable(store.problems()?),
+                scheduler,
+                &calendar,
+                config.scheduler.daily_limit,
+                clock,
+            );
+            print_problems(&with_difficulty(todays_problems, difficulty), &calendar);
+            Ok(())
+        }
+        Command::Done {
+            lc_number,
+            rating,
+            minutes,
+            notes,
+        } => {
+            let updated = done(
+                store,
+                scheduler,
+                lc_number,
+                rating,
+                minutes.map(|m| m * 60),
+                notes.as_deref(),
+                clock,
+            )?;
+            println!(
+                "Marked {}: {} as {}, next due {}",
+                updated.lc_number,
+                updated.problem_name,
+                rating.as_str(),
+                calendar.format_date(updated.next_due)
+            );
+            Ok(())
+        }
+        Command::Notes { lc_number, text } => notes(store, lc_number, text.as_deref()),
+        Command::Solution {
+            lc_number,
+            path,
+            language,
+        } => {
+            let solution = save_solution(store, lc_number, &path, language.as_deref(), clock)?;
+            println!(
+                "Saved {} as version {} of the {} solution to {lc_number}",
+                path.display(),
+                solution.version,
+                solution.language
+            );
+            Ok(())
+        }
+        Command::Solutions { lc_number, version } => {
+            let problem = find_problem(store, lc_number)?;
+            let solutions = readable(store.solutions_for_problem(&problem.id)?);
+            match version {
+                Some(version) => {
+                    let solution = solutions
+                        .iter()
+                        .find(|solution| solution.version == version)
+                        .ok_or_else(|| {
+                            Error::validation(format!(
+                                "{lc_number} has no solution version {version}"
+                            ))
+                        })?;
+                    println!("{}", solution.code.trim_end());
+                }
+                None => {
+                    for solution in &solutions {
+                        println!(
+                            "v{:<3} {:<12} {:>5} lines, saved {}",
+                            solution.version,
+                            solution.language,
+                            solution.code.lines().count(),
+                            calendar.format_date(solution.created_at)
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::Search { query } => {
+            let problems = readable(store.problems()?);
+            let reviews = readable(store.reviews()?);
+            for problem in search_problems(&problems, &reviews, &query) {
+                print_problems(std::slice::from_ref(&problem), &calendar);
+                if let Some(notes) = &problem.notes {
+                    for line in notes.lines() {
+                        println!("       {line}");
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::Stats => stats(store, scheduler, &calendar, config, clock),
+        Command::Optimize => {
+            let fitted = fsrs::refit(store, clock)?;
+            if fitted.weights == DEFAULT_WEIGHTS {
+                println!(
+                    "Kept the default FSRS weights, fitting them to {} reviews didn't improve on them",
+                    fitted.review_count
+                );
+            } else {
+                println!("Fitted the FSRS weights to {} reviews", fitted.review_count);
+            }
+            if config.scheduler.algorithm != SchedulerKind::Fsrs {
+                println!("They are used once scheduler.algorithm is set to \"fsrs\"");
+            }
+            Ok(())
+        }
+        Command::Heatmap { path } => {
+            let today = calendar.today(clock.now());
+            let (from, to) = Heatmap::review_range(&calendar, today);
+            let heatmap = Heatmap::new(
+                &readable(store.reviews_between(from, to)?),
+                &calendar,
+                today,
+            );
+            fs::write(&path, heatmap.to_svg())
+                .context(format!("Could not write {}", path.display()))?;
+            println!(
+                "Wrote {} reviews on {} days to {}",
+                heatmap.total(),
+                heatmap.active_days(),
+                path.display()
+            );
+            Ok(())
+        }
+        Command::Seed { list, dry_run } => seed(store, config, list, dry_run, clock),
+        Command::Export { path, format } => {
+            let format = format
+                .or_else(|| Format::from_path(&path))
+                .ok_or_else(unknown_format)?;
+            let summary = export(store, &path, format, clock)?;
+            warn_skipped(&summary.skipped);
+            match format {
+                Format::Json => println!(
+                    "Exported {} problems and {} reviews to {}",
+                    summary.problems,
+                    summary.reviews,
+                    path.display()
+                ),
+                Format::Csv => println!(
+                    "Exported {} problems to {} and {} reviews to {}",
+                    summary.problems,
+                    path.display(),
+                    summary.reviews,
+                    reviews_csv_path(&path).display()
+                ),
+            }
+            Ok(())
+        }
+        Command::Import {
+            path,
+            format,
+            on_duplicate,
+            dry_run,
+        } => {
+            let format = format
+                .or_else(|| Format::from_path(&path))
+                .ok_or_else(unknown_format)?;
+            let summary = import(
+                store,
+                &path,
+                format,
+                on_duplicate,
+                &config.categories,
+                dry_run,
+                clock,
+            )?;
+            print_import_summary(&summary.problems, summary.reviews_added, dry_run);
+            Ok(())
+        }
+    }
+}
+
+fn print_import_summary(problems: &[(ImportAction, String)], reviews_added: usize, dry_run: bool) {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for (action, description) in problems {
+        let verb = match action {
+            ImportAction::Add => "add",
+            ImportAction::Skip => "skip",
+            ImportAction::Overwrite => "overwrite",
+            ImportAction::Merge => "merge",
+        };
+        *counts.entry(verb).or_default() += 1;
+        println!("{verb:<10} {description}");
+    }
+
+    let totals: Vec<String> = counts
+        .iter()
+        .map(|(verb, count)| format!("{verb} {count}"))
+        .collect();
+    println!(
+        "{}{}, {} new reviews",
+        if dry_run {
+            "Dry run, nothing written: "
+        } else {
+            ""
+        },
+        if totals.is_empty() {
+            "no problems".to_string()
+        } else {
+            totals.join(", ")
+        },
+        reviews_added
+    );
+}
+
+fn unknown_format() -> Error {
+    Error::validation("Could not tell the format from the file extension, pass --format")
+}
+
+fn with_difficulty(problems: Vec<LCProblem>, difficulty: Option<Difficulty>) -> Vec<LCProblem> {
+    match difficulty {
+        Some(difficulty) => problems
+            .into_iter()
+            .filter(|problem| problem.lc_difficulty == Some(difficulty))
+            .collect(),
+        None => problems,
+    }
+}
+
+// Rows that couldn't be read are left out with a warning rather than failing the command
+fn readable<T>(rows: Rows<T>) -> Vec<T> {
+    warn_skipped(&rows.skipped);
+    rows.rows
+}
+
+fn warn_skipped(skipped: &[Error]) {
+    for err in skipped {
+        eprintln!("Warning: {err}");
+    }
+}
+
+fn add(
+    store: &dyn ProblemStore,
+    config: &Config,
+    lc_number: u32,
+    name: &str,
+    category: &str,
+    difficulty: Option<Difficulty>,
+    clock: &dyn Clock,
+) -> Result<()> {
+    if !type_validator(category, &config.categories) {
+        return Err(Error::validation(format!(
+            "Unknown category \"{}\". Expected one of: {}",
+            category,
+            config.categories.join(", ")
+        )));
+    }
+    if store.problem_exists(lc_number, name)? {
+        return Err(Error::validation(format!(
+            "Problem {lc_number} ({name}) already exists"
+        )));
+    }
+
+    let difficulty =
+        difficulty.or_else(|| catalog::lookup(lc_number).map(|entry| entry.difficulty));
+    store.add_problem(lc_number, name, category, difficulty, clock)?;
+    match difficulty {
+        Some(difficulty) => println!("Added {lc_number}: {name} ({category}, {difficulty})"),
+        None => println!("Added {lc_number}: {name} ({category})"),
+    }
+    Ok(())
+}
+
+// Adds every catalog problem from the list that isn't in the database yet
+fn seed(
+    store: &dyn ProblemStore,
+    config: &Config,
+    list: ProblemList,
+    dry_run: bool,
+    clock: &dyn Clock,
+) -> Result<()> {
+    let (mut added, mut existing, mut uncategorized) = (0, 0, 0);
+
+    store.atomically(&mut || {
+        for entry in catalog().iter().filter(|entry| entry.in_list(list)) {
+            if !type_validator(&entry.problem_type, &config.categories) {
+                uncategorized += 1;
+            } else if store.problem_exists(entry.lc_number, &entry.problem_name)? {
+                existing += 1;
+            } else {
+                store.add_problem(
+                    entry.lc_number,
+                    &entry.problem_name,
+                    &entry.problem_type,
+                    Some(entry.difficulty),
+                    clock,
+                )?;
+                println!("add        {}: {}", entry.lc_number, entry.problem_name);
+                added += 1;
+            }
+        }
+        Ok(!dry_run)
+    })?;
+    println!(
+        "{}Added {} problems, {} already existed",
+        if dry_run {
+            "Dry run, nothing written: "
+        } else {
+            ""
+        },
+        added,
+        existing
+    );
+    if uncategorized > 0 {
+        println!(
+            "Skipped {uncategorized} problems whose category isn't in the configured categories"
+        );
+    }
+    Ok(())
+}
+
+// Records the review and returns the problem with its new schedule
+fn done(
+    store: &dyn ProblemStore,
+    scheduler: &dyn Scheduler,
+    lc_number: u32,
+    rating: Rating,
+    time_spent: Option<u32>,
+    notes: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<LCProblem> {
+    let problem = find_problem(store, lc_number)?;
+
+    store.complete_problem(&problem, rating, time_spent, notes, scheduler, clock)
+}
+
+fn find_problem(store: &dyn ProblemStore, lc_number: u32) -> Result<LCProblem> {
+    store
+        .problem_by_number(lc_number)?
+        .ok_or_else(|| Error::validation(format!("No problem with number {lc_number}")))
+}
+
+fn save_solution(
+    store: &dyn ProblemStore,
+    lc_number: u32,
+    path: &Path,
+    language: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<Solution> {
+    let problem = find_problem(store, lc_number)?;
+    // known languages are stored under one name whether given by name or extension
+    let language = match language {
+        Some(language) => language
+            .parse::<Language>()
+            .map_or(language.to_lowercase(), |known| known.as_str().to_string()),
+        None => Language::from_path(path)
+            .map(|known| known.as_str().to_string())
+            .ok_or_else(|| {
+                Error::validation(format!(
+                    "Could not tell the language of {}, pass --language",
+                    path.display()
+                ))
+            })?,
+    };
+    let code = fs::read_to_string(path).context(format!("Could not read {}", path.display()))?;
+    store.add_solution(&problem, &language, &code, clock)
+}
+
+fn notes(store: &dyn ProblemStore, lc_number: u32, text: Option<&str>) -> Result<()> {
+    let problem = find_problem(store, lc_number)?;
+
+    match text {
+        None => match &problem.notes {
+            Some(notes) => println!("{notes}"),
+            None => println!("No notes for {lc_number}: {}", problem.problem_name),
+        },
+        Some(text) => {
+            let notes = Some(text.trim()).filter(|notes| !notes.is_empty());
+            store.update_notes(&problem.id, notes)?;
+            println!(
+                "{} the notes of {lc_number}: {}",
+                if notes.is_some() { "Saved" } else { "Cleared" },
+                problem.problem_name
+            );
+        }
+    }
+    Ok(())
+}
+
+fn stats(
+    store: &dyn ProblemStore,
+    scheduler: &dyn Scheduler,
+    calendar: &Calendar,
+    config: &Config,
+    clock: &dyn Clock,
+) -> Result<()> {
+    let problems = readable(store.problems()?);
+    let reviews = readable(store.reviews()?);
+    let todays_problems = get_todays_problems(
+        &problems,
+        scheduler,
+        calendar,
+        config.scheduler.daily_limit,
+        clock,
+    );
+
+    let now = clock.now();
+    let today = calendar.today(now);
+    let (start_of_day, end_of_day) = calendar.day_range(today);
+    // unreadable rows were already reported by the full scan above
+    let reviews_today = store.reviews_between(start_of_day, end_of_day)?.rows;
+
+    println!("Problems:      {}", problems.len());
+    println!("Reviews:       {}", reviews.len());
+    println!("Due today:     {}", todays_problems.len());
+    println!("Done today:    {}", reviews_today.len());
+
+    let practiced_at: Vec<_> = reviews.iter().map(|review| review.reviewed_at).collect();
+    let streaks = compute_streaks(
+        &practiced_at,
+        calendar,
+        today,
+        config.streaks.freezes_per_month,
+    );
+    println!(
+        "Streak:        {} days (best {}, {} freezes left this month)",
+        streaks.current, streaks.longest, streaks.freezes_left
+    );
+    if let Some(interval) = average_interval(&problems) {
+        println!("Avg interval:  {interval:.1} days");
+    }
+
+    print_group_stats(
+        "Category",
+        category_stats(&problems, &config.categories, calendar, now),
+    );
+    print_group_stats("Difficulty", difficulty_stats(&problems, calendar, now));
+    Ok(())
+}
+
+// A table of the non-empty groups, headed by `group`
+fn print_group_stats(group: &str, stats: Vec<CategoryStats>) {
+    let stats: Vec<_> = stats
+        .into_iter()
+        .filter(|stats| stats.problems > 0)
+        .collect();
+    if stats.is_empty() {
+        return;
+    }
+    println!();
+    println!(
+        "{:<26} {:>8} {:>8} {:>8} {:>8}",
+        group, "Problems", "Reviews", "Mastered", "Overdue"
+    );
+    for stats in stats {
+        println!(
+            "{:<26} {:>8} {:>8} {:>7.0}% {:>8}",
+            stats.category,
+            stats.problems,
+            stats.reviews,
+            stats.mastery() * 100.0,
+            stats.overdue
+        );
+    }
+}
+
+fn print_problems(problems: &[LCProblem], calendar: &Calendar) {
+    for problem in problems {
+        println!(
+            "{:>5}  {:<40} {:<24} {:<6} practiced {:>2}x, due {}",
+            problem.lc_number,
+            problem.problem_name,
+            problem.problem_type,
+            problem
+                .lc_difficulty
+                .map_or("-", |difficulty| difficulty.as_str()),
+            problem.times_practiced,
+            calendar.format_date(problem.next_due)
+        );
+    }
+}
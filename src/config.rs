@@ -0,0 +1,442 @@
+use crate::{
+    db::models::Difficulty,
+    error::{Context, Error, Result},
+    language::Language,
+    scheduler::{fsrs::DEFAULT_DESIRED_RETENTION, SchedulerKind},
+    validation::DEFAULT_CATEGORIES,
+};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const APP_DIR: &str = "rusty_lcurve";
+const CONFIG_FILE: &str = "config.toml";
+const DB_FILE: &str = "rusty_l_db.db";
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub db_path: Option<PathBuf>,
+    pub categories: Vec<String>,
+    pub scheduler: SchedulerConfig,
+    pub streaks: StreakConfig,
+    pub time: TimeConfig,
+    pub session: SessionConfig,
+    pub editor: EditorConfig,
+    pub ui: UiConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerConfig {
+    pub algorithm: SchedulerKind,
+    pub sample_size: usize,         // problems the heuristic samples each day
+    pub daily_limit: Option<usize>, // cap on Todays Problems, the heuristic shows 3 when unset
+    pub desired_retention: f64,     // FSRS recall probability to schedule reviews at
+    pub difficulty_prior: bool,     // scale first intervals by problem difficulty
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StreakConfig {
+    pub freezes_per_month: u32, // missed days per month that don't break a streak
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeConfig {
+    pub timezone: Option<Tz>, // IANA name like "America/Los_Angeles", the system zone if unset
+    pub day_starts_at: u32,   // hour after midnight when a new practice day begins
+}
+
+// Countdown limits in minutes for practice sessions. The per-difficulty limits apply to
+// problems whose difficulty is known and fall back to time_limit; no countdown when unset.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    pub time_limit: Option<u32>,
+    pub easy: Option<u32>,
+    pub medium: Option<u32>,
+    pub hard: Option<u32>,
+}
+
+impl SessionConfig {
+    pub fn time_limit_for(&self, difficulty: Option<Difficulty>) -> Option<u32> {
+        let by_difficulty = match difficulty {
+            Some(Difficulty::Easy) => self.easy,
+            Some(Difficulty::Medium) => self.medium,
+            Some(Difficulty::Hard) => self.hard,
+            None => None,
+        };
+        by_difficulty.or(self.time_limit)
+    }
+}
+
+// Opening a problem in an external editor from the TUI
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EditorConfig {
+    pub command: Option<String>, // e.g. "code --wait", $VISUAL or $EDITOR when unset
+    pub scratch_dir: PathBuf,    // where solution scaffolds are written
+    pub language: Language,      // the scaffold's language
+}
+
+impl EditorConfig {
+    // The configured command, then $VISUAL, then $EDITOR, then vi
+    pub fn command(&self) -> String {
+        self.command
+            .clone()
+            .or_else(|| env::var("VISUAL").ok())
+            .or_else(|| env::var("EDITOR").ok())
+            .filter(|command| !command.trim().is_empty())
+            .unwrap_or_else(|| "vi".to_string())
+    }
+
+    pub fn scratch_dir(&self) -> PathBuf {
+        expand_home(&self.scratch_dir)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    pub tick_rate_ms: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            db_path: None,
+            categories: DEFAULT_CATEGORIES.iter().map(|c| c.to_string()).collect(),
+            scheduler: SchedulerConfig::default(),
+            streaks: StreakConfig::default(),
+            time: TimeConfig::default(),
+            session: SessionConfig::default(),
+            editor: EditorConfig::default(),
+            ui: UiConfig::default(),
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: SchedulerKind::Sm2,
+            sample_size: 10,
+            daily_limit: None,
+            desired_retention: DEFAULT_DESIRED_RETENTION,
+            difficulty_prior: false,
+        }
+    }
+}
+
+impl Default for StreakConfig {
+    fn default() -> Self {
+        Self {
+            freezes_per_month: 2,
+        }
+    }
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            scratch_dir: PathBuf::from("~/lcurve"),
+            language: Language::Rust,
+        }
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self { tick_rate_ms: 250 }
+    }
+}
+
+impl Config {
+    // Loads $XDG_CONFIG_HOME/rusty_lcurve/config.toml, or the defaults if there isn't one
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let contents =
+            fs::read_to_string(&path).context(format!("Could not read {}", path.display()))?;
+        Config::parse(&contents).map_err(|err| {
+            Error::Config(format!("Invalid config file {}: {}", path.display(), err))
+        })
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(contents).map_err(|err| err.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.categories.is_empty() {
+            return Err("categories must not be empty".to_string());
+        }
+        let mut seen = HashSet::new();
+        for category in &self.categories {
+            if category.trim().is_empty() {
+                return Err("categories must not contain blank names".to_string());
+            }
+            if !seen.insert(category) {
+                return Err(format!("category \"{category}\" is listed twice"));
+            }
+        }
+
+        if self.scheduler.sample_size == 0 {
+            return Err("scheduler.sample_size must be at least 1".to_string());
+        }
+        if self.scheduler.daily_limit == Some(0) {
+            return Err("scheduler.daily_limit must be at least 1".to_string());
+        }
+        let retention = self.scheduler.desired_retention;
+        if !(0.5..1.0).contains(&retention) {
+            return Err(format!(
+                "scheduler.desired_retention must be at least 0.5 and below 1.0, got {retention}"
+            ));
+        }
+
+        if self.streaks.freezes_per_month > 31 {
+            return Err("streaks.freezes_per_month must be at most 31".to_string());
+        }
+
+        if self.time.day_starts_at > 23 {
+            return Err(format!(
+                "time.day_starts_at must be an hour from 0 to 23, got {}",
+                self.time.day_starts_at
+            ));
+        }
+
+        let limits = [
+            ("time_limit", self.session.time_limit),
+            ("easy", self.session.easy),
+            ("medium", self.session.medium),
+            ("hard", self.session.hard),
+        ];
+        if let Some((key, _)) = limits.iter().find(|(_, limit)| *limit == Some(0)) {
+            return Err(format!("session.{key} must be at least 1 minute"));
+        }
+
+        if self
+            .editor
+            .command
+            .as_ref()
+            .is_some_and(|command| command.trim().is_empty())
+        {
+            return Err("editor.command must not be blank".to_string());
+        }
+
+        if self.ui.tick_rate_ms == 0 {
+            return Err("ui.tick_rate_ms must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+// The --db flag (or RUSTY_LCURVE_DB) wins over the config file, which wins over the
+// XDG data directory. The parent directory is created if it doesn't exist yet.
+pub fn resolve_db_path(cli_db: Option<PathBuf>, config: &Config) -> Result<PathBuf> {
+    let db_path = match cli_db.or_else(|| config.db_path.as_deref().map(expand_home)) {
+        Some(path) => path,
+        None => xdg_dir("XDG_DATA_HOME", ".local/share")
+            .ok_or_else(|| {
+                Error::Config("Could not find a data directory, set HOME or pass --db".to_string())
+            })?
+            .join(APP_DIR)
+            .join(DB_FILE),
+    };
+
+    if let Some(parent) = db_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent).context(format!("Could not create {}", parent.display()))?;
+        }
+    }
+    Ok(db_path)
+}
+
+// Where the database used to live before the path was configurable
+pub fn legacy_db_path() -> PathBuf {
+    PathBuf::from(DB_FILE)
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(APP_DIR).join(CONFIG_FILE))
+}
+
+fn xdg_dir(var: &str, home_fallback: &str) -> Option<PathBuf> {
+    match env::var_os(var) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => home_dir().map(|home| home.join(home_fallback)),
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn error(contents: &str) -> String {
+        Config::parse(contents).unwrap_err()
+    }
+
+    #[test]
+    fn an_empty_file_gives_the_defaults() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.categories.len(), DEFAULT_CATEGORIES.len());
+        assert_eq!(config.scheduler.algorithm, SchedulerKind::Sm2);
+        assert_eq!(config.editor.language, Language::Rust);
+        assert_eq!(config.ui.tick_rate_ms, 250);
+    }
+
+    #[test]
+    fn parses_every_section() {
+        let config = Config::parse(
+            r#"
+            db_path = "/data/lcurve.db"
+            categories = ["Arrays & Hashing", "Graphs"]
+
+            [scheduler]
+            algorithm = "fsrs"
+            daily_limit = 5
+            desired_retention = 0.85
+
+            [time]
+            timezone = "America/Los_Angeles"
+            day_starts_at = 4
+
+            [session]
+            time_limit = 25
+            hard = 40
+
+            [editor]
+            command = "code --wait"
+            language = "python"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.db_path, Some(PathBuf::from("/data/lcurve.db")));
+        assert_eq!(config.categories, vec!["Arrays & Hashing", "Graphs"]);
+        assert_eq!(config.scheduler.algorithm, SchedulerKind::Fsrs);
+        assert_eq!(config.scheduler.daily_limit, Some(5));
+        assert_eq!(config.time.timezone, Some(chrono_tz::America::Los_Angeles));
+        assert_eq!(
+            config.session.time_limit_for(Some(Difficulty::Hard)),
+            Some(40)
+        );
+        assert_eq!(
+            config.session.time_limit_for(Some(Difficulty::Easy)),
+            Some(25)
+        );
+        assert_eq!(config.editor.command(), "code --wait");
+        assert_eq!(config.editor.language, Language::Python);
+        // sections left out keep their defaults
+        assert_eq!(config.streaks.freezes_per_month, 2);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(error("colour = true").contains("unknown field"));
+        assert!(error("[scheduler]\nalgoritm = \"fsrs\"").contains("unknown field"));
+        assert!(error("[editor]\nlanguage = \"cobol\"").contains("unknown variant"));
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        for (contents, message) in [
+            ("categories = []", "categories must not be empty"),
+            ("categories = [\"Graphs\", \" \"]", "blank names"),
+            (
+                "categories = [\"Graphs\", \"Graphs\"]",
+                "\"Graphs\" is listed twice",
+            ),
+            (
+                "[scheduler]\nsample_size = 0",
+                "sample_size must be at least 1",
+            ),
+            (
+                "[scheduler]\ndaily_limit = 0",
+                "daily_limit must be at least 1",
+            ),
+            ("[scheduler]\ndesired_retention = 1.0", "desired_retention"),
+            ("[scheduler]\ndesired_retention = 0.4", "desired_retention"),
+            ("[streaks]\nfreezes_per_month = 32", "freezes_per_month"),
+            (
+                "[time]\nday_starts_at = 24",
+                "day_starts_at must be an hour",
+            ),
+            (
+                "[session]\nmedium = 0",
+                "session.medium must be at least 1 minute",
+            ),
+            (
+                "[editor]\ncommand = \"  \"",
+                "editor.command must not be blank",
+            ),
+            ("[ui]\ntick_rate_ms = 0", "tick_rate_ms must be at least 1"),
+        ] {
+            let err = error(contents);
+            assert!(err.contains(message), "{contents:?} gave {err:?}");
+        }
+        assert!(Config::parse("[time]\nday_starts_at = 23").is_ok());
+        assert!(Config::parse("[scheduler]\ndesired_retention = 0.5").is_ok());
+    }
+
+    #[test]
+    fn db_path_comes_from_the_flag_then_the_config_then_xdg() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            db_path: Some(dir.path().join("config/lcurve.db")),
+            ..Config::default()
+        };
+
+        let flag = dir.path().join("flag/lcurve.db");
+        assert_eq!(resolve_db_path(Some(flag.clone()), &config).unwrap(), flag);
+        assert!(dir.path().join("flag").is_dir());
+
+        assert_eq!(
+            resolve_db_path(None, &config).unwrap(),
+            dir.path().join("config/lcurve.db")
+        );
+
+        // only this test reads XDG_DATA_HOME, so setting it can't race with the others
+        env::set_var("XDG_DATA_HOME", dir.path().join("data"));
+        assert_eq!(
+            resolve_db_path(None, &Config::default()).unwrap(),
+            dir.path().join("data").join(APP_DIR).join(DB_FILE)
+        );
+    }
+
+    #[test]
+    fn a_leading_tilde_is_the_home_directory() {
+        let Some(home) = home_dir() else {
+            return;
+        };
+        assert_eq!(expand_home(Path::new("~/lcurve")), home.join("lcurve"));
+        assert_eq!(expand_home(Path::new("/tmp/~")), PathBuf::from("/tmp/~"));
+    }
+}
@@ -1,36 +1,144 @@
-use crate::db::models::LCProblem;
-use chrono::Utc;
-use rusqlite::{params, Connection, Result};
-use std::fs;
-
-pub fn init_db(db_path: &str) -> Result<()> {
-    if !fs::metadata(db_path).is_ok() {
-        let conn = Connection::open(db_path)?;
-        create_table(&conn)?;
+use crate::{
+    db::{
+        migrations::migrate,
+        models::{Difficulty, LCProblem, Review, Solution},
+    },
+    error::{self, Context},
+    scheduler::fsrs::FittedWeights,
+};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, types::Type, Connection, Params, Result, Row, Statement};
+use std::{error::Error, path::Path, str::FromStr};
+
+// Creates the database if needed and brings its schema up to date
+pub fn init_db(db_path: &Path) -> error::Result<()> {
+    let mut conn = Connection::open(db_path)
+        .context(format!("Could not open database {}", db_path.display()))?;
+    migrate(&mut conn, db_path)
+}
+
+// The rows a query could read, plus an error for each row that couldn't be, so one
+// corrupt row doesn't hide the rest
+#[derive(Debug)]
+pub struct Rows<T> {
+    pub rows: Vec<T>,
+    pub skipped: Vec<error::Error>,
+}
+
+impl<T> Default for Rows<T> {
+    fn default() -> Self {
+        Self {
+            rows: vec![],
+            skipped: vec![],
+        }
     }
-    Ok(())
 }
 
-pub fn get_connection(db_path: &str) -> Result<Connection> {
-    Connection::open(db_path)
+impl<T> Rows<T> {
+    // The row a lookup asked for. If it couldn't be read, that is the error.
+    pub fn single(self) -> error::Result<Option<T>> {
+        match (
+            self.rows.into_iter().next(),
+            self.skipped.into_iter().next(),
+        ) {
+            (Some(row), _) => Ok(Some(row)),
+            (None, Some(err)) => Err(err),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+// Runs the query and converts each row, skipping (and recording) the ones that fail.
+// The first selected column must be the row's id so skipped rows can be named.
+fn query_rows<T>(
+    query: &mut Statement,
+    params: impl Params,
+    table: &str,
+    from_row: fn(&Row) -> Result<T>,
+) -> Result<Rows<T>> {
+    let mut loaded = Rows::default();
+    let mut rows = query.query(params)?;
+    while let Some(row) = rows.next()? {
+        match from_row(row) {
+            Ok(item) => loaded.rows.push(item),
+            Err(err) => {
+                let id = row.get::<_, String>(0).unwrap_or_else(|_| "?".to_string());
+                let message = match &err {
+                    rusqlite::Error::FromSqlConversionFailure(index, _, cause) => format!(
+                        "bad {}: {}",
+                        row.as_ref().column_name(*index).unwrap_or("column"),
+                        cause
+                    ),
+                    err => err.to_string(),
+                };
+                loaded.skipped.push(error::Error::parse(
+                    format!("Could not read {table} {id}"),
+                    message,
+                ));
+            }
+        }
+    }
+    Ok(loaded)
+}
+
+const PROBLEM_COLUMNS: &str = "id, lc_number, problem_name, problem_type, start_date, last_practiced, times_practiced, ease_factor, interval, repetitions, next_due, stability, difficulty, last_rating, lc_difficulty, notes";
+
+pub fn get_all_problems(conn: &Connection) -> Result<Rows<LCProblem>> {
+    let mut query = conn.prepare(&format!("SELECT {PROBLEM_COLUMNS} FROM problems"))?;
+    query_rows(&mut query, [], "problem", problem_from_row)
+}
+
+// Lookups return Rows too, so a matching row that can't be read is reported by its id
+// instead of failing with a bare conversion error or passing for a missing problem
+pub fn get_problem(conn: &Connection, problem_id: &str) -> Result<Rows<LCProblem>> {
+    let mut query = conn.prepare(&format!(
+        "SELECT {PROBLEM_COLUMNS} FROM problems WHERE id = ?1"
+    ))?;
+    query_rows(&mut query, params![problem_id], "problem", problem_from_row)
+}
+
+pub fn get_problem_by_number(conn: &Connection, lc_number: u32) -> Result<Rows<LCProblem>> {
+    let mut query = conn.prepare(&format!(
+        "SELECT {PROBLEM_COLUMNS} FROM problems WHERE lc_number = ?1 LIMIT 1"
+    ))?;
+    query_rows(&mut query, params![lc_number], "problem", problem_from_row)
 }
 
-pub fn get_all_problems(conn: &Connection) -> Result<Vec<LCProblem>> {
-    let mut query = conn.prepare("SELECT id, lc_number, problem_name, problem_type, start_date, last_practiced, times_practiced FROM problems")?;
-    let problems = query
-        .query_map([], |row| {
-            Ok(LCProblem {
-                id: row.get(0)?,
-                lc_number: row.get(1)?,
-                problem_name: row.get(2)?,
-                problem_type: row.get(3)?,
-                start_date: row.get::<_, String>(4)?.parse().unwrap(),
-                last_practiced: row.get::<_, String>(5)?.parse().unwrap(),
-                times_practiced: row.get(6)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(problems)
+fn problem_from_row(row: &Row) -> Result<LCProblem> {
+    Ok(LCProblem {
+        id: row.get(0)?,
+        lc_number: row.get(1)?,
+        problem_name: row.get(2)?,
+        problem_type: row.get(3)?,
+        start_date: parse_column(row, 4)?,
+        last_practiced: parse_column(row, 5)?,
+        times_practiced: row.get(6)?,
+        ease_factor: row.get(7)?,
+        interval: row.get(8)?,
+        repetitions: row.get(9)?,
+        next_due: parse_column(row, 10)?,
+        stability: row.get(11)?,
+        difficulty: row.get(12)?,
+        last_rating: row
+            .get::<_, Option<String>>(13)?
+            .and_then(|rating| rating.parse().ok()),
+        lc_difficulty: row
+            .get::<_, Option<String>>(14)?
+            .and_then(|difficulty| difficulty.parse().ok()),
+        notes: row.get(15)?,
+    })
+}
+
+// Timestamps and ratings are stored as text; one that doesn't parse is reported as a
+// conversion error on its column instead of panicking
+fn parse_column<T>(row: &Row, index: usize) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn Error + Send + Sync>>,
+{
+    row.get::<_, String>(index)?.parse().map_err(|err: T::Err| {
+        rusqlite::Error::FromSqlConversionFailure(index, Type::Text, err.into())
+    })
 }
 
 pub fn problem_exists(conn: &Connection, lc_number: u32, problem_name: &str) -> Result<bool> {
@@ -41,16 +149,43 @@ pub fn problem_exists(conn: &Connection, lc_number: u32, problem_name: &str) ->
     Ok(count > 0)
 }
 
-// The necessity of having an LCProblem struct is a bit questionable
-pub fn insert_problem(
+// Like problem_exists, but ignores the problem being edited
+pub fn other_problem_exists(
     conn: &Connection,
+    problem_id: &str,
     lc_number: u32,
     problem_name: &str,
-    problem_type: &str,
-) -> Result<()> {
-    let lc_problem = LCProblem::new(lc_number, problem_name, problem_type);
+) -> Result<bool> {
+    let mut stmt = conn.prepare(
+        "SELECT COUNT(1) FROM problems WHERE (lc_number = ?1 OR problem_name = ?2) AND id != ?3",
+    )?;
+
+    let count: u32 = stmt.query_row(params![lc_number, problem_name, problem_id], |row| {
+        row.get(0)
+    })?;
+    Ok(count > 0)
+}
+
+// The problem problem_exists would have matched on, if any
+pub fn get_matching_problem(
+    conn: &Connection,
+    lc_number: u32,
+    problem_name: &str,
+) -> Result<Rows<LCProblem>> {
+    let mut query = conn.prepare(&format!(
+        "SELECT {PROBLEM_COLUMNS} FROM problems WHERE lc_number = ?1 OR problem_name = ?2 LIMIT 1"
+    ))?;
+    query_rows(
+        &mut query,
+        params![lc_number, problem_name],
+        "problem",
+        problem_from_row,
+    )
+}
+
+pub fn insert_lc_problem(conn: &Connection, lc_problem: &LCProblem) -> Result<()> {
     conn.execute(
-        "INSERT INTO problems (id, lc_number, problem_name, problem_type, start_date, last_practiced, times_practiced) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO problems (id, lc_number, problem_name, problem_type, start_date, last_practiced, times_practiced, ease_factor, interval, repetitions, next_due, stability, difficulty, last_rating, lc_difficulty, notes) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
         params![
             lc_problem.id,
             lc_problem.lc_number,
@@ -59,33 +194,249 @@ pub fn insert_problem(
             lc_problem.start_date.to_string(),
             lc_problem.last_practiced.to_string(),
             lc_problem.times_practiced,
+            lc_problem.ease_factor,
+            lc_problem.interval,
+            lc_problem.repetitions,
+            lc_problem.next_due.to_string(),
+            lc_problem.stability,
+            lc_problem.difficulty,
+            lc_problem.last_rating.map(|rating| rating.as_str()),
+            lc_problem.lc_difficulty.map(|difficulty| difficulty.as_str()),
+            lc_problem.notes,
         ]
     )?;
     Ok(())
 }
 
-fn create_table(conn: &Connection) -> Result<()> {
+pub fn update_problem(
+    conn: &Connection,
+    problem_id: &str,
+    lc_number: u32,
+    problem_name: &str,
+    problem_type: &str,
+    lc_difficulty: Option<Difficulty>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE problems SET lc_number = ?1, problem_name = ?2, problem_type = ?3, lc_difficulty = ?4 WHERE id = ?5",
+        params![
+            lc_number,
+            problem_name,
+            problem_type,
+            lc_difficulty.map(|difficulty| difficulty.as_str()),
+            problem_id
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn update_notes(conn: &Connection, problem_id: &str, notes: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE problems SET notes = ?1 WHERE id = ?2",
+        params![notes, problem_id],
+    )?;
+    Ok(())
+}
+
+// Overwrites every column of the problem with the same id
+pub fn replace_problem(conn: &Connection, problem: &LCProblem) -> Result<()> {
+    conn.execute(
+        "UPDATE problems SET lc_number = ?1, problem_name = ?2, problem_type = ?3, start_date = ?4, last_practiced = ?5, times_practiced = ?6, ease_factor = ?7, interval = ?8, repetitions = ?9, next_due = ?10, stability = ?11, difficulty = ?12, last_rating = ?13, lc_difficulty = ?14, notes = ?15 WHERE id = ?16",
+        params![
+            problem.lc_number,
+            problem.problem_name,
+            problem.problem_type,
+            problem.start_date.to_string(),
+            problem.last_practiced.to_string(),
+            problem.times_practiced,
+            problem.ease_factor,
+            problem.interval,
+            problem.repetitions,
+            problem.next_due.to_string(),
+            problem.stability,
+            problem.difficulty,
+            problem.last_rating.map(|rating| rating.as_str()),
+            problem.lc_difficulty.map(|difficulty| difficulty.as_str()),
+            problem.notes,
+            problem.id,
+        ],
+    )?;
+    Ok(())
+}
+
+// Removes the problem along with its review log and solutions
+pub fn delete_problem(conn: &Connection, problem_id: &str) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM reviews WHERE problem_id = ?1",
+        params![problem_id],
+    )?;
+    tx.execute(
+        "DELETE FROM solutions WHERE problem_id = ?1",
+        params![problem_id],
+    )?;
+    tx.execute("DELETE FROM problems WHERE id = ?1", params![problem_id])?;
+    tx.commit()
+}
+
+// Saves the problem's new schedule and appends the review, in one transaction
+pub fn record_review(conn: &Connection, problem: &LCProblem, review: &Review) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE problems SET last_practiced = ?1, times_practiced = ?2, ease_factor = ?3, interval = ?4, repetitions = ?5, next_due = ?6, stability = ?7, difficulty = ?8, last_rating = ?9 WHERE id = ?10",
+        params![
+            problem.last_practiced.to_string(),
+            problem.times_practiced,
+            problem.ease_factor,
+            problem.interval,
+            problem.repetitions,
+            problem.next_due.to_string(),
+            problem.stability,
+            problem.difficulty,
+            problem.last_rating.map(|rating| rating.as_str()),
+            problem.id,
+        ])?;
+    insert_review(&tx, review)?;
+    tx.commit()
+}
+
+// Returns false if a review with the same id is already logged
+pub fn insert_review(conn: &Connection, review: &Review) -> Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO reviews (id, problem_id, reviewed_at, rating, time_spent, notes) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            review.id,
+            review.problem_id,
+            review.reviewed_at.to_string(),
+            review.rating.as_str(),
+            review.time_spent,
+            review.notes,
+        ],
+    )?;
+    Ok(inserted > 0)
+}
+
+pub fn get_all_reviews(conn: &Connection) -> Result<Rows<Review>> {
+    let mut query = conn.prepare(
+        "SELECT id, problem_id, reviewed_at, rating, time_spent, notes FROM reviews ORDER BY reviewed_at",
+    )?;
+    query_rows(&mut query, [], "review", review_from_row)
+}
+
+pub fn get_reviews_for_problem(conn: &Connection, problem_id: &str) -> Result<Rows<Review>> {
+    let mut query = conn.prepare(
+        "SELECT id, problem_id, reviewed_at, rating, time_spent, notes FROM reviews WHERE problem_id = ?1 ORDER BY reviewed_at",
+    )?;
+    query_rows(&mut query, params![problem_id], "review", review_from_row)
+}
+
+// Reviews with from <= reviewed_at < to
+pub fn get_reviews_between(
+    conn: &Connection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Rows<Review>> {
+    let mut query = conn.prepare(
+        "SELECT id, problem_id, reviewed_at, rating, time_spent, notes FROM reviews WHERE reviewed_at >= ?1 AND reviewed_at < ?2 ORDER BY reviewed_at",
+    )?;
+    query_rows(
+        &mut query,
+        params![from.to_string(), to.to_string()],
+        "review",
+        review_from_row,
+    )
+}
+
+fn review_from_row(row: &Row) -> Result<Review> {
+    Ok(Review {
+        id: row.get(0)?,
+        problem_id: row.get(1)?,
+        reviewed_at: parse_column(row, 2)?,
+        rating: parse_column(row, 3)?,
+        time_spent: row.get(4)?,
+        notes: row.get(5)?,
+    })
+}
+
+pub fn count_reviews(conn: &Connection) -> Result<u32> {
+    conn.query_row("SELECT COUNT(1) FROM reviews", [], |row| row.get(0))
+}
+
+pub fn get_fsrs_weights(conn: &Connection) -> Result<Option<FittedWeights>> {
+    let mut query =
+        conn.prepare("SELECT weights, review_count, fitted_at FROM fsrs_weights WHERE id = 1")?;
+    let mut rows = query.query([])?;
+    let Some(row) = rows.next()? else {
+        return Ok(None);
+    };
+    // Stored as the 17 weights separated by commas
+    let weights: String = row.get(0)?;
+    let weights = weights
+        .split(',')
+        .map(|weight| weight.trim().parse::<f64>().ok())
+        .collect::<Option<Vec<f64>>>()
+        .and_then(|weights| <[f64; 17]>::try_from(weights).ok())
+        .ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                0,
+                Type::Text,
+                "expected 17 comma separated weights".into(),
+            )
+        })?;
+    Ok(Some(FittedWeights {
+        weights,
+        review_count: row.get(1)?,
+        fitted_at: parse_column(row, 2)?,
+    }))
+}
+
+pub fn save_fsrs_weights(conn: &Connection, fitted: &FittedWeights) -> Result<()> {
+    let weights: Vec<String> = fitted.weights.iter().map(f64::to_string).collect();
     conn.execute(
-        "
-        CREATE TABLE IF NOT EXISTS problems (
-            id TEXT PRIMARY KEY,
-            lc_number INTEGER NOT NULL,
-            problem_name TEXT NOT NULL,
-            problem_type TEXT NOT NULL,
-            start_date TEXT NOT NULL,
-            last_practiced TEXT NOT NULL,
-            times_practiced INTEGER NOT NULL
-        );
-        ",
-        [],
+        "INSERT OR REPLACE INTO fsrs_weights (id, weights, review_count, fitted_at) VALUES (1, ?1, ?2, ?3)",
+        params![
+            weights.join(","),
+            fitted.review_count,
+            fitted.fitted_at.to_string()
+        ],
     )?;
     Ok(())
 }
 
-pub fn update_problem_as_completed(conn: &Connection, problem_id: &str) -> Result<()> {
-    let now = Utc::now();
+pub fn insert_solution(conn: &Connection, solution: &Solution) -> Result<()> {
     conn.execute(
-        "UPDATE problems SET last_practiced = ?1, times_practiced = times_practiced + 1 WHERE id = ?2",
-        params![now.to_string(), problem_id])?;
+        "INSERT INTO solutions (id, problem_id, version, language, code, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            solution.id,
+            solution.problem_id,
+            solution.version,
+            solution.language,
+            solution.code,
+            solution.created_at.to_string(),
+        ],
+    )?;
     Ok(())
 }
+
+// Oldest version first
+pub fn get_solutions_for_problem(conn: &Connection, problem_id: &str) -> Result<Rows<Solution>> {
+    let mut query = conn.prepare(
+        "SELECT id, problem_id, version, language, code, created_at FROM solutions WHERE problem_id = ?1 ORDER BY version",
+    )?;
+    query_rows(
+        &mut query,
+        params![problem_id],
+        "solution",
+        solution_from_row,
+    )
+}
+
+fn solution_from_row(row: &Row) -> Result<Solution> {
+    Ok(Solution {
+        id: row.get(0)?,
+        problem_id: row.get(1)?,
+        version: row.get(2)?,
+        language: row.get(3)?,
+        code: row.get(4)?,
+        created_at: parse_column(row, 5)?,
+    })
+}
@@ -0,0 +1,375 @@
+use crate::{
+    catalog,
+    error::{self, Context, Error},
+};
+use rusqlite::{Connection, Result};
+use std::{fs, path::Path};
+
+// Ordered schema migrations. Migration i brings the database from user_version i to i + 1,
+// so new migrations are only ever appended.
+const MIGRATIONS: [fn(&Connection) -> Result<()>; 8] = [
+    create_problems_table,
+    add_sm2_columns,
+    add_fsrs_columns,
+    create_reviews_table,
+    add_lc_difficulty_column,
+    add_problem_notes_column,
+    create_solutions_table,
+    create_fsrs_weights_table,
+];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.len() as u32
+}
+
+// Runs every pending migration in a single transaction, backing up the database file first
+pub fn migrate(conn: &mut Connection, db_path: &Path) -> error::Result<()> {
+    let version: u32 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .context("Could not read the database schema version")?;
+    if version > latest_version() {
+        return Err(Error::validation(format!(
+            "Database schema version {} is newer than this build supports ({})",
+            version,
+            latest_version()
+        )));
+    }
+    if version == latest_version() {
+        return Ok(());
+    }
+
+    if has_tables(conn).context("Could not read the database schema")? {
+        let mut backup_path = db_path.as_os_str().to_owned();
+        backup_path.push(format!(".v{version}.bak"));
+        fs::copy(db_path, &backup_path).context(format!(
+            "Could not back up {} before migrating",
+            db_path.display()
+        ))?;
+    }
+
+    let mut migrated = || -> Result<()> {
+        let tx = conn.transaction()?;
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(&tx)?;
+        }
+        tx.pragma_update(None, "user_version", latest_version())?;
+        tx.commit()
+    };
+    migrated().context(format!(
+        "Could not migrate the database from schema version {version} to {}",
+        latest_version()
+    ))
+}
+
+fn has_tables(conn: &Connection) -> Result<bool> {
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(1) FROM sqlite_master WHERE type = 'table'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+// Databases created before migrations existed may already have some of these columns
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT COUNT(1) FROM pragma_table_info('{table}') WHERE name = ?1"),
+        [column],
+        |row| row.get::<_, u32>(0).map(|count| count > 0),
+    )?;
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+fn create_problems_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "
+        CREATE TABLE IF NOT EXISTS problems (
+            id TEXT PRIMARY KEY,
+            lc_number INTEGER NOT NULL,
+            problem_name TEXT NOT NULL,
+            problem_type TEXT NOT NULL,
+            start_date TEXT NOT NULL,
+            last_practiced TEXT NOT NULL,
+            times_practiced INTEGER NOT NULL
+        );
+        ",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_sm2_columns(conn: &Connection) -> Result<()> {
+    add_column(conn, "problems", "ease_factor", "REAL NOT NULL DEFAULT 2.5")?;
+    add_column(conn, "problems", "interval", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column(
+        conn,
+        "problems",
+        "repetitions",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column(conn, "problems", "next_due", "TEXT NOT NULL DEFAULT ''")?;
+    add_column(conn, "problems", "last_rating", "TEXT")?;
+    // Existing problems are due right away
+    conn.execute(
+        "UPDATE problems SET next_due = last_practiced WHERE next_due = ''",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_fsrs_columns(conn: &Connection) -> Result<()> {
+    add_column(conn, "problems", "stability", "REAL NOT NULL DEFAULT 0")?;
+    add_column(conn, "problems", "difficulty", "REAL NOT NULL DEFAULT 0")?;
+    Ok(())
+}
+
+fn create_reviews_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "
+        CREATE TABLE IF NOT EXISTS reviews (
+            id TEXT PRIMARY KEY,
+            problem_id TEXT NOT NULL REFERENCES problems(id) ON DELETE CASCADE,
+            reviewed_at TEXT NOT NULL,
+            rating TEXT NOT NULL,
+            time_spent INTEGER,
+            notes TEXT
+        );
+        ",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS reviews_problem_id ON reviews (problem_id, reviewed_at)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_lc_difficulty_column(conn: &Connection) -> Result<()> {
+    add_column(conn, "problems", "lc_difficulty", "TEXT")?;
+    // Problems from the catalog get its difficulty, the rest stay unknown until edited
+    let mut stmt = conn.prepare(
+        "UPDATE problems SET lc_difficulty = ?1 WHERE lc_number = ?2 AND lc_difficulty IS NULL",
+    )?;
+    for entry in catalog::catalog() {
+        stmt.execute((entry.difficulty.as_str(), entry.lc_number))?;
+    }
+    Ok(())
+}
+
+fn add_problem_notes_column(conn: &Connection) -> Result<()> {
+    add_column(conn, "problems", "notes", "TEXT")
+}
+
+fn create_solutions_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "
+        CREATE TABLE IF NOT EXISTS solutions (
+            id TEXT PRIMARY KEY,
+            problem_id TEXT NOT NULL REFERENCES problems(id) ON DELETE CASCADE,
+            version INTEGER NOT NULL,
+            language TEXT NOT NULL,
+            code TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        ",
+        [],
+    )?;
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS solutions_problem_version ON solutions (problem_id, version)",
+        [],
+    )?;
+    Ok(())
+}
+
+// A single row holding the FSRS weights last fitted to the review log
+fn create_fsrs_weights_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "
+        CREATE TABLE IF NOT EXISTS fsrs_weights (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            weights TEXT NOT NULL,
+            review_count INTEGER NOT NULL,
+            fitted_at TEXT NOT NULL
+        );
+        ",
+        [],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        models::Difficulty,
+        store::{ProblemStore, SqliteStore},
+    };
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    // The problems table as it was before any migration existed
+    const BASELINE_SCHEMA: &str = "
+        CREATE TABLE problems (
+            id TEXT PRIMARY KEY,
+            lc_number INTEGER NOT NULL,
+            problem_name TEXT NOT NULL,
+            problem_type TEXT NOT NULL,
+            start_date TEXT NOT NULL,
+            last_practiced TEXT NOT NULL,
+            times_practiced INTEGER NOT NULL
+        );
+        INSERT INTO problems VALUES ('p1', 42, 'Trapping Rain Water', 'Two Pointers',
+            '2024-03-01 09:00:00 UTC', '2024-03-05 09:00:00 UTC', 3);
+    ";
+
+    // Scheduling columns some builds put straight into CREATE TABLE before migrations
+    // existed, so those databases are still at user_version 0. The last of them also
+    // created the reviews table.
+    const PRE_MIGRATION_COLUMNS: [&str; 3] = [
+        "ease_factor REAL NOT NULL, interval INTEGER NOT NULL, repetitions INTEGER NOT NULL, next_due TEXT NOT NULL",
+        "ease_factor REAL NOT NULL, interval INTEGER NOT NULL, repetitions INTEGER NOT NULL, next_due TEXT NOT NULL, last_rating TEXT",
+        "ease_factor REAL NOT NULL, interval INTEGER NOT NULL, repetitions INTEGER NOT NULL, next_due TEXT NOT NULL, stability REAL NOT NULL DEFAULT 0, difficulty REAL NOT NULL DEFAULT 0, last_rating TEXT",
+    ];
+
+    fn database(dir: &TempDir, schema: &str) -> (Connection, PathBuf) {
+        let path = dir.path().join("lcurve.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(schema).unwrap();
+        (conn, path)
+    }
+
+    fn version(conn: &Connection) -> u32 {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    fn backups(dir: &TempDir) -> Vec<String> {
+        let mut backups: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".bak"))
+            .collect();
+        backups.sort();
+        backups
+    }
+
+    #[test]
+    fn upgrades_a_baseline_database_and_backs_it_up() {
+        let dir = TempDir::new().unwrap();
+        let (mut conn, path) = database(&dir, BASELINE_SCHEMA);
+
+        migrate(&mut conn, &path).unwrap();
+        assert_eq!(version(&conn), latest_version());
+        assert_eq!(backups(&dir), vec!["lcurve.db.v0.bak"]);
+
+        // the backup is the database as it was, not the migrated one
+        let backup = Connection::open(dir.path().join("lcurve.db.v0.bak")).unwrap();
+        assert_eq!(version(&backup), 0);
+        let columns: u32 = backup
+            .query_row(
+                "SELECT COUNT(1) FROM pragma_table_info('problems')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(columns, 7);
+
+        drop(conn);
+        let store = SqliteStore::open(&path).unwrap();
+        let problems = store.problems().unwrap();
+        assert!(problems.skipped.is_empty());
+        let problem = &problems.rows[0];
+        assert_eq!(problem.times_practiced, 3);
+        assert_eq!(problem.next_due, problem.last_practiced);
+        assert_eq!(problem.lc_difficulty, Some(Difficulty::Hard));
+        assert!(store.reviews().unwrap().rows.is_empty());
+    }
+
+    #[test]
+    fn upgrades_databases_that_already_have_scheduling_columns() {
+        for (i, columns) in PRE_MIGRATION_COLUMNS.iter().enumerate() {
+            let dir = TempDir::new().unwrap();
+            let (mut conn, path) = database(
+                &dir,
+                &format!(
+                    "
+                    CREATE TABLE problems (
+                        id TEXT PRIMARY KEY,
+                        lc_number INTEGER NOT NULL,
+                        problem_name TEXT NOT NULL,
+                        problem_type TEXT NOT NULL,
+                        start_date TEXT NOT NULL,
+                        last_practiced TEXT NOT NULL,
+                        times_practiced INTEGER NOT NULL,
+                        {columns}
+                    );
+                    INSERT INTO problems (id, lc_number, problem_name, problem_type, start_date,
+                        last_practiced, times_practiced, ease_factor, interval, repetitions, next_due)
+                    VALUES ('p1', 42, 'Trapping Rain Water', 'Two Pointers',
+                        '2024-03-01 09:00:00 UTC', '2024-03-05 09:00:00 UTC', 3, 2.36, 6, 2,
+                        '2024-03-11 09:00:00 UTC');
+                    "
+                ),
+            );
+            if i == PRE_MIGRATION_COLUMNS.len() - 1 {
+                create_reviews_table(&conn).unwrap();
+            }
+
+            migrate(&mut conn, &path).unwrap();
+            assert_eq!(version(&conn), latest_version());
+            drop(conn);
+
+            let store = SqliteStore::open(&path).unwrap();
+            let problems = store.problems().unwrap();
+            assert!(problems.skipped.is_empty(), "{:?}", problems.skipped);
+            let problem = &problems.rows[0];
+            assert_eq!((problem.ease_factor, problem.interval), (2.36, 6));
+            assert_eq!(
+                problem.next_due.to_string(),
+                "2024-03-11 09:00:00 UTC",
+                "schema {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn migrating_twice_is_a_no_op() {
+        let dir = TempDir::new().unwrap();
+        let (mut conn, path) = database(&dir, BASELINE_SCHEMA);
+        migrate(&mut conn, &path).unwrap();
+        let before = fs::read(&path).unwrap();
+
+        migrate(&mut conn, &path).unwrap();
+        assert_eq!(version(&conn), latest_version());
+        assert_eq!(backups(&dir), vec!["lcurve.db.v0.bak"]);
+        assert_eq!(fs::read(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn new_databases_are_not_backed_up() {
+        let dir = TempDir::new().unwrap();
+        let (mut conn, path) = database(&dir, "");
+
+        migrate(&mut conn, &path).unwrap();
+        assert_eq!(version(&conn), latest_version());
+        assert!(backups(&dir).is_empty());
+    }
+
+    #[test]
+    fn refuses_a_newer_schema() {
+        let dir = TempDir::new().unwrap();
+        let (mut conn, path) = database(&dir, BASELINE_SCHEMA);
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+
+        assert!(migrate(&mut conn, &path).is_err());
+        assert_eq!(version(&conn), latest_version() + 1);
+        assert!(backups(&dir).is_empty());
+    }
+}
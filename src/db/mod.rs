@@ -1,2 +1,5 @@
+#[allow(clippy::module_inception)]
 pub mod db;
+mod migrations;
 pub mod models;
+pub mod store;
@@ -1,29 +1,160 @@
+use crate::scheduler::sm2::DEFAULT_EASE_FACTOR;
 use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::{fmt, str::FromStr};
 use uuid::Uuid;
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Rating {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Rating {
+    pub const ALL: [Rating; 4] = [Rating::Again, Rating::Hard, Rating::Good, Rating::Easy];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rating::Again => "Again",
+            Rating::Hard => "Hard",
+            Rating::Good => "Good",
+            Rating::Easy => "Easy",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rating::Again => "Failed, couldn't solve it",
+            Rating::Hard => "Solved, but needed hints",
+            Rating::Good => "Solved unaided",
+            Rating::Easy => "Solved right away",
+        }
+    }
+}
+
+impl FromStr for Rating {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "again" => Ok(Rating::Again),
+            "hard" => Ok(Rating::Hard),
+            "good" => Ok(Rating::Good),
+            "easy" => Ok(Rating::Easy),
+            _ => Err(format!("Unknown rating: {s}")),
+        }
+    }
+}
+
+// LeetCode's difficulty label, not to be confused with FSRS's difficulty in LCProblem
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!(
+                "Unknown difficulty: {s} (expected easy, medium or hard)"
+            )),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct LCProblem {
     pub id: String,
     pub lc_number: u32,
     pub problem_name: String,
     pub problem_type: String,
+    pub lc_difficulty: Option<Difficulty>, // unknown for problems outside the catalog
     pub start_date: DateTime<Utc>,
     pub last_practiced: DateTime<Utc>,
     pub times_practiced: u32,
+    pub ease_factor: f64,
+    pub interval: u32,    // days until the next review
+    pub repetitions: u32, // consecutive successful reviews, reset on a failed recall
+    pub next_due: DateTime<Utc>,
+    pub stability: f64, // FSRS memory state, 0 until the first FSRS review
+    pub difficulty: f64,
+    pub last_rating: Option<Rating>,
+    pub notes: Option<String>, // the key insight, free-form and possibly multi-line
 }
 
-impl LCProblem {
-    pub fn new(lc_number: u32, problem_name: &str, problem_type: &str) -> Self {
-        let current_time = Utc::now();
+#[derive(Debug, PartialEq, Clone)]
+pub struct Review {
+    pub id: String,
+    pub problem_id: String,
+    pub reviewed_at: DateTime<Utc>,
+    pub rating: Rating,
+    pub time_spent: Option<u32>, // seconds
+    pub notes: Option<String>,
+}
 
+// One saved version of our own solution to a problem. Versions are never overwritten,
+// a new one is added each time.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Solution {
+    pub id: String,
+    pub problem_id: String,
+    pub version: u32, // 1 for the problem's first solution
+    pub language: String,
+    pub code: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LCProblem {
+    pub fn new(
+        lc_number: u32,
+        problem_name: &str,
+        problem_type: &str,
+        current_time: DateTime<Utc>,
+    ) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             lc_number,
             problem_name: problem_name.to_string(),
             problem_type: problem_type.to_string(),
+            lc_difficulty: None,
             start_date: current_time,
             last_practiced: current_time,
             times_practiced: 0,
+            ease_factor: DEFAULT_EASE_FACTOR,
+            interval: 0,
+            repetitions: 0,
+            next_due: current_time,
+            stability: 0.0,
+            difficulty: 0.0,
+            last_rating: None,
+            notes: None,
         }
     }
 }
@@ -0,0 +1,1134 @@
+use crate::{
+    clock::Clock,
+    db::{
+        db::{
+            count_reviews, delete_problem, get_all_problems, get_all_reviews, get_fsrs_weights,
+            get_matching_problem, get_problem, get_problem_by_number, get_reviews_between,
+            get_reviews_for_problem, get_solutions_for_problem, init_db, insert_lc_problem,
+            insert_review, insert_solution, other_problem_exists, problem_exists, record_review,
+            replace_problem, save_fsrs_weights, update_notes, update_problem, Rows,
+        },
+        migrations::migrate,
+        models::{Difficulty, LCProblem, Rating, Review, Solution},
+    },
+    error::{Context, Error, Result},
+    scheduler::{fsrs::FittedWeights, Scheduler},
+};
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+};
+use uuid::Uuid;
+
+// Everything the app needs from wherever problems and their review history are kept.
+// Listing methods skip rows that can't be read and return them in Rows::skipped.
+pub trait ProblemStore {
+    // The file backing the store, None if nothing is written to disk
+    fn path(&self) -> Option<PathBuf>;
+
+    fn problems(&self) -> Result<Rows<LCProblem>>;
+    fn problem(&self, problem_id: &str) -> Result<Option<LCProblem>>;
+    fn problem_by_number(&self, lc_number: u32) -> Result<Option<LCProblem>>;
+    // The problem problem_exists would have matched on, if any
+    fn matching_problem(&self, lc_number: u32, problem_name: &str) -> Result<Option<LCProblem>>;
+    // True if a problem has the same number or name
+    fn problem_exists(&self, lc_number: u32, problem_name: &str) -> Result<bool>;
+    // Like problem_exists, but ignores the problem being edited
+    fn other_problem_exists(
+        &self,
+        problem_id: &str,
+        lc_number: u32,
+        problem_name: &str,
+    ) -> Result<bool>;
+
+    fn insert_problem(&self, problem: &LCProblem) -> Result<()>;
+    fn update_problem(
+        &self,
+        problem_id: &str,
+        lc_number: u32,
+        problem_name: &str,
+        problem_type: &str,
+        lc_difficulty: Option<Difficulty>,
+    ) -> Result<()>;
+    // Overwrites every field of the problem with the same id
+    fn replace_problem(&self, problem: &LCProblem) -> Result<()>;
+    // None clears the notes
+    fn update_notes(&self, problem_id: &str, notes: Option<&str>) -> Result<()>;
+    // Removes the problem along with its review log and solutions
+    fn delete_problem(&self, problem_id: &str) -> Result<()>;
+    // Saves the problem's new schedule and appends the review, both or neither
+    fn record_review(&self, problem: &LCProblem, review: &Review) -> Result<()>;
+    // Logs a review without touching its problem. Returns false if one with the same id
+    // is already logged.
+    fn insert_review(&self, review: &Review) -> Result<bool>;
+
+    // Review history, oldest first
+    fn reviews(&self) -> Result<Rows<Review>>;
+    fn reviews_for_problem(&self, problem_id: &str) -> Result<Rows<Review>>;
+    // Reviews with from <= reviewed_at < to
+    fn reviews_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Rows<Review>>;
+    // Size of the review log, counting rows that can't be read
+    fn review_count(&self) -> Result<u32>;
+
+    fn insert_solution(&self, solution: &Solution) -> Result<()>;
+    // Every saved version, oldest first
+    fn solutions_for_problem(&self, problem_id: &str) -> Result<Rows<Solution>>;
+
+    // The FSRS weights last fitted to the review log, None if they never were
+    fn fsrs_weights(&self) -> Result<Option<FittedWeights>>;
+    fn save_fsrs_weights(&self, fitted: &FittedWeights) -> Result<()>;
+
+    // Runs `write` so its changes are kept all together or not at all. They are rolled
+    // back if it fails or returns false, which is how dry runs see their own changes.
+    fn atomically(&self, write: &mut dyn FnMut() -> Result<bool>) -> Result<()>;
+
+    fn add_problem(
+        &self,
+        lc_number: u32,
+        problem_name: &str,
+        problem_type: &str,
+        lc_difficulty: Option<Difficulty>,
+        clock: &dyn Clock,
+    ) -> Result<()> {
+        self.insert_problem(&LCProblem {
+            lc_difficulty,
+            ..LCProblem::new(lc_number, problem_name, problem_type, clock.now())
+        })
+    }
+
+    // Applies a review to the problem's schedule and logs it. Returns the updated problem.
+    fn complete_problem(
+        &self,
+        problem: &LCProblem,
+        rating: Rating,
+        time_spent: Option<u32>,
+        notes: Option<&str>,
+        scheduler: &dyn Scheduler,
+        clock: &dyn Clock,
+    ) -> Result<LCProblem> {
+        let mut problem = problem.clone();
+        scheduler.review(&mut problem, rating, clock.now());
+        let review = Review {
+            id: Uuid::new_v4().to_string(),
+            problem_id: problem.id.clone(),
+            reviewed_at: problem.last_practiced,
+            rating,
+            time_spent,
+            notes: notes.map(|notes| notes.to_string()),
+        };
+        self.record_review(&problem, &review)?;
+        Ok(problem)
+    }
+
+    // Saves the code as the problem's newest solution and returns it
+    fn add_solution(
+        &self,
+        problem: &LCProblem,
+        language: &str,
+        code: &str,
+        clock: &dyn Clock,
+    ) -> Result<Solution> {
+        let latest = self
+            .solutions_for_problem(&problem.id)?
+            .rows
+            .iter()
+            .map(|solution| solution.version)
+            .max();
+        let solution = Solution {
+            id: Uuid::new_v4().to_string(),
+            problem_id: problem.id.clone(),
+            version: latest.unwrap_or(0) + 1,
+            language: language.to_string(),
+            code: code.to_string(),
+            created_at: clock.now(),
+        };
+        self.insert_solution(&solution)?;
+        Ok(solution)
+    }
+}
+
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    // Creates the database if needed and brings its schema up to date
+    pub fn open(db_path: &Path) -> Result<Self> {
+        init_db(db_path)?;
+        Ok(Self {
+            conn: Connection::open(db_path)
+                .context(format!("Could not open database {}", db_path.display()))?,
+        })
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        let mut conn =
+            Connection::open_in_memory().context("Could not open an in-memory database")?;
+        migrate(&mut conn, Path::new(":memory:"))?;
+        Ok(Self { conn })
+    }
+
+    // For SQLite-only work the trait doesn't cover
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl ProblemStore for SqliteStore {
+    fn path(&self) -> Option<PathBuf> {
+        self.conn
+            .path()
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    }
+
+    fn problems(&self) -> Result<Rows<LCProblem>> {
+        get_all_problems(&self.conn).context("Could not load problems")
+    }
+
+    fn problem(&self, problem_id: &str) -> Result<Option<LCProblem>> {
+        get_problem(&self.conn, problem_id)
+            .context(format!("Could not load problem {problem_id}"))?
+            .single()
+    }
+
+    fn problem_by_number(&self, lc_number: u32) -> Result<Option<LCProblem>> {
+        get_problem_by_number(&self.conn, lc_number)
+            .context(format!("Could not load problem {lc_number}"))?
+            .single()
+    }
+
+    fn matching_problem(&self, lc_number: u32, problem_name: &str) -> Result<Option<LCProblem>> {
+        get_matching_problem(&self.conn, lc_number, problem_name)
+            .context(format!(
+                "Could not look up problem {lc_number}: {problem_name}"
+            ))?
+            .single()
+    }
+
+    fn problem_exists(&self, lc_number: u32, problem_name: &str) -> Result<bool> {
+        problem_exists(&self.conn, lc_number, problem_name)
+            .context("Could not check whether the problem already exists")
+    }
+
+    fn other_problem_exists(
+        &self,
+        problem_id: &str,
+        lc_number: u32,
+        problem_name: &str,
+    ) -> Result<bool> {
+        other_problem_exists(&self.conn, problem_id, lc_number, problem_name)
+            .context("Could not check whether the problem already exists")
+    }
+
+    fn insert_problem(&self, problem: &LCProblem) -> Result<()> {
+        insert_lc_problem(&self.conn, problem).context(format!(
+            "Could not add problem {}: {}",
+            problem.lc_number, problem.problem_name
+        ))
+    }
+
+    fn update_problem(
+        &self,
+        problem_id: &str,
+        lc_number: u32,
+        problem_name: &str,
+        problem_type: &str,
+        lc_difficulty: Option<Difficulty>,
+    ) -> Result<()> {
+        update_problem(
+            &self.conn,
+            problem_id,
+            lc_number,
+            problem_name,
+            problem_type,
+            lc_difficulty,
+        )
+        .context(format!(
+            "Could not save problem {lc_number}: {problem_name}"
+        ))
+    }
+
+    fn replace_problem(&self, problem: &LCProblem) -> Result<()> {
+        replace_problem(&self.conn, problem).context(format!(
+            "Could not save problem {}: {}",
+            problem.lc_number, problem.problem_name
+        ))
+    }
+
+    fn update_notes(&self, problem_id: &str, notes: Option<&str>) -> Result<()> {
+        update_notes(&self.conn, problem_id, notes)
+            .context(format!("Could not save the notes of problem {problem_id}"))
+    }
+
+    fn delete_problem(&self, problem_id: &str) -> Result<()> {
+        delete_problem(&self.conn, problem_id)
+            .context(format!("Could not delete problem {problem_id}"))
+    }
+
+    fn record_review(&self, problem: &LCProblem, review: &Review) -> Result<()> {
+        record_review(&self.conn, problem, review).context(format!(
+            "Could not record the review of problem {}",
+            problem.lc_number
+        ))
+    }
+
+    fn insert_review(&self, review: &Review) -> Result<bool> {
+        insert_review(&self.conn, review).context(format!("Could not log review {}", review.id))
+    }
+
+    fn reviews(&self) -> Result<Rows<Review>> {
+        get_all_reviews(&self.conn).context("Could not load reviews")
+    }
+
+    fn reviews_for_problem(&self, problem_id: &str) -> Result<Rows<Review>> {
+        get_reviews_for_problem(&self.conn, problem_id).context(format!(
+            "Could not load the reviews of problem {problem_id}"
+        ))
+    }
+
+    fn reviews_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Rows<Review>> {
+        get_reviews_between(&self.conn, from, to).context("Could not load reviews")
+    }
+
+    fn review_count(&self) -> Result<u32> {
+        count_reviews(&self.conn).context("Could not count reviews")
+    }
+
+    fn insert_solution(&self, solution: &Solution) -> Result<()> {
+        insert_solution(&self.conn, solution).context(format!(
+            "Could not save version {} of the solution",
+            solution.version
+        ))
+    }
+
+    fn solutions_for_problem(&self, problem_id: &str) -> Result<Rows<Solution>> {
+        get_solutions_for_problem(&self.conn, problem_id).context(format!(
+            "Could not load the solutions of problem {problem_id}"
+        ))
+    }
+
+    fn fsrs_weights(&self) -> Result<Option<FittedWeights>> {
+        get_fsrs_weights(&self.conn).context("Could not load the fitted FSRS weights")
+    }
+
+    fn save_fsrs_weights(&self, fitted: &FittedWeights) -> Result<()> {
+        save_fsrs_weights(&self.conn, fitted).context("Could not save the fitted FSRS weights")
+    }
+
+    // Everything `write` does goes through this connection, so it all lands in the transaction
+    fn atomically(&self, write: &mut dyn FnMut() -> Result<bool>) -> Result<()> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .context("Could not start a transaction")?;
+        if write()? {
+            tx.commit().context("Could not commit the transaction")?;
+        }
+        Ok(())
+    }
+}
+
+// Keeps everything in memory, for tests and demo mode. Problems stay in insertion order
+// and reviews are sorted by time, like the SQLite queries return them.
+#[derive(Default)]
+pub struct MemoryStore {
+    problems: RefCell<Vec<LCProblem>>,
+    reviews: RefCell<Vec<Review>>,
+    solutions: RefCell<Vec<Solution>>,
+    fsrs_weights: RefCell<Option<FittedWeights>>,
+}
+
+impl MemoryStore {
+    fn reviews_where(&self, keep: impl Fn(&Review) -> bool) -> Rows<Review> {
+        Rows {
+            rows: self
+                .reviews
+                .borrow()
+                .iter()
+                .filter(|review| keep(review))
+                .cloned()
+                .collect(),
+            skipped: vec![],
+        }
+    }
+}
+
+impl ProblemStore for MemoryStore {
+    fn path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    fn problems(&self) -> Result<Rows<LCProblem>> {
+        Ok(Rows {
+            rows: self.problems.borrow().clone(),
+            skipped: vec![],
+        })
+    }
+
+    fn problem(&self, problem_id: &str) -> Result<Option<LCProblem>> {
+        Ok(self
+            .problems
+            .borrow()
+            .iter()
+            .find(|problem| problem.id == problem_id)
+            .cloned())
+    }
+
+    fn problem_by_number(&self, lc_number: u32) -> Result<Option<LCProblem>> {
+        Ok(self
+            .problems
+            .borrow()
+            .iter()
+            .find(|problem| problem.lc_number == lc_number)
+            .cloned())
+    }
+
+    fn matching_problem(&self, lc_number: u32, problem_name: &str) -> Result<Option<LCProblem>> {
+        Ok(self
+            .problems
+            .borrow()
+            .iter()
+            .find(|problem| problem.lc_number == lc_number || problem.problem_name == problem_name)
+            .cloned())
+    }
+
+    fn problem_exists(&self, lc_number: u32, problem_name: &str) -> Result<bool> {
+        Ok(self
+            .problems
+            .borrow()
+            .iter()
+            .any(|problem| problem.lc_number == lc_number || problem.problem_name == problem_name))
+    }
+
+    fn other_problem_exists(
+        &self,
+        problem_id: &str,
+        lc_number: u32,
+        problem_name: &str,
+    ) -> Result<bool> {
+        Ok(self.problems.borrow().iter().any(|problem| {
+            problem.id != problem_id
+                && (problem.lc_number == lc_number || problem.problem_name == problem_name)
+        }))
+    }
+
+    fn insert_problem(&self, problem: &LCProblem) -> Result<()> {
+        let mut problems = self.problems.borrow_mut();
+        if problems.iter().any(|existing| existing.id == problem.id) {
+            return Err(Error::validation(format!(
+                "A problem with id {} already exists",
+                problem.id
+            )));
+        }
+        problems.push(problem.clone());
+        Ok(())
+    }
+
+    fn update_problem(
+        &self,
+        problem_id: &str,
+        lc_number: u32,
+        problem_name: &str,
+        problem_type: &str,
+        lc_difficulty: Option<Difficulty>,
+    ) -> Result<()> {
+        if let Some(problem) = self
+            .problems
+            .borrow_mut()
+            .iter_mut()
+            .find(|problem| problem.id == problem_id)
+        {
+            problem.lc_number = lc_number;
+            problem.problem_name = problem_name.to_string();
+            problem.problem_type = problem_type.to_string();
+            problem.lc_difficulty = lc_difficulty;
+        }
+        Ok(())
+    }
+
+    fn replace_problem(&self, problem: &LCProblem) -> Result<()> {
+        if let Some(stored) = self
+            .problems
+            .borrow_mut()
+            .iter_mut()
+            .find(|stored| stored.id == problem.id)
+        {
+            *stored = problem.clone();
+        }
+        Ok(())
+    }
+
+    fn update_notes(&self, problem_id: &str, notes: Option<&str>) -> Result<()> {
+        if let Some(problem) = self
+            .problems
+            .borrow_mut()
+            .iter_mut()
+            .find(|problem| problem.id == problem_id)
+        {
+            problem.notes = notes.map(str::to_string);
+        }
+        Ok(())
+    }
+
+    fn delete_problem(&self, problem_id: &str) -> Result<()> {
+        self.reviews
+            .borrow_mut()
+            .retain(|review| review.problem_id != problem_id);
+        self.solutions
+            .borrow_mut()
+            .retain(|solution| solution.problem_id != problem_id);
+        self.problems
+            .borrow_mut()
+            .retain(|problem| problem.id != problem_id);
+        Ok(())
+    }
+
+    fn record_review(&self, problem: &LCProblem, review: &Review) -> Result<()> {
+        let mut problems = self.problems.borrow_mut();
+        let Some(stored) = problems.iter_mut().find(|stored| stored.id == problem.id) else {
+            return Err(Error::validation(format!(
+                "No problem with id {}",
+                problem.id
+            )));
+        };
+        *stored = problem.clone();
+        self.insert_review(review)?;
+        Ok(())
+    }
+
+    fn insert_review(&self, review: &Review) -> Result<bool> {
+        let mut reviews = self.reviews.borrow_mut();
+        if reviews.iter().any(|logged| logged.id == review.id) {
+            return Ok(false);
+        }
+        let index = reviews.partition_point(|logged| logged.reviewed_at <= review.reviewed_at);
+        reviews.insert(index, review.clone());
+        Ok(true)
+    }
+
+    fn reviews(&self) -> Result<Rows<Review>> {
+        Ok(self.reviews_where(|_| true))
+    }
+
+    fn reviews_for_problem(&self, problem_id: &str) -> Result<Rows<Review>> {
+        Ok(self.reviews_where(|review| review.problem_id == problem_id))
+    }
+
+    fn reviews_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Rows<Review>> {
+        Ok(self.reviews_where(|review| review.reviewed_at >= from && review.reviewed_at < to))
+    }
+
+    fn review_count(&self) -> Result<u32> {
+        Ok(self.reviews.borrow().len() as u32)
+    }
+
+    fn insert_solution(&self, solution: &Solution) -> Result<()> {
+        let mut solutions = self.solutions.borrow_mut();
+        if solutions.iter().any(|saved| {
+            saved.problem_id == solution.problem_id && saved.version == solution.version
+        }) {
+            return Err(Error::validation(format!(
+                "Version {} of the solution already exists",
+                solution.version
+            )));
+        }
+        solutions.push(solution.clone());
+        Ok(())
+    }
+
+    fn solutions_for_problem(&self, problem_id: &str) -> Result<Rows<Solution>> {
+        let mut solutions: Vec<Solution> = self
+            .solutions
+            .borrow()
+            .iter()
+            .filter(|solution| solution.problem_id == problem_id)
+            .cloned()
+            .collect();
+        solutions.sort_by_key(|solution| solution.version);
+        Ok(Rows {
+            rows: solutions,
+            skipped: vec![],
+        })
+    }
+
+    fn fsrs_weights(&self) -> Result<Option<FittedWeights>> {
+        Ok(self.fsrs_weights.borrow().clone())
+    }
+
+    fn save_fsrs_weights(&self, fitted: &FittedWeights) -> Result<()> {
+        *self.fsrs_weights.borrow_mut() = Some(fitted.clone());
+        Ok(())
+    }
+
+    fn atomically(&self, write: &mut dyn FnMut() -> Result<bool>) -> Result<()> {
+        let problems = self.problems.borrow().clone();
+        let reviews = self.reviews.borrow().clone();
+        let solutions = self.solutions.borrow().clone();
+        let fsrs_weights = self.fsrs_weights.borrow().clone();
+        let result = write();
+        if !matches!(result, Ok(true)) {
+            *self.problems.borrow_mut() = problems;
+            *self.reviews.borrow_mut() = reviews;
+            *self.solutions.borrow_mut() = solutions;
+            *self.fsrs_weights.borrow_mut() = fsrs_weights;
+        }
+        result.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calendar::Calendar,
+        clock::FixedClock,
+        config::Config,
+        exchange::{
+            import::{import, DuplicateStrategy, ImportAction, ImportSummary},
+            Format,
+        },
+        scheduler::{
+            fsrs::DEFAULT_WEIGHTS, heuristic::Heuristic, prior::DifficultyPrior, sm2::Sm2,
+        },
+        utils::get_todays_problems,
+    };
+    use chrono::{TimeDelta, TimeZone};
+    use chrono_tz::Tz;
+    use std::{collections::BTreeMap, fs};
+    use tempfile::TempDir;
+
+    // Every test runs against both backends so they can't drift apart
+    fn stores() -> Vec<Box<dyn ProblemStore>> {
+        vec![
+            Box::new(SqliteStore::in_memory().unwrap()),
+            Box::new(MemoryStore::default()),
+        ]
+    }
+
+    // 9am UTC on 2024-03-01
+    fn clock_at_start() -> FixedClock {
+        FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap())
+    }
+
+    fn utc() -> Calendar {
+        Calendar::new(Some(Tz::UTC), 0)
+    }
+
+    fn add_problems(store: &dyn ProblemStore, count: u32, clock: &FixedClock) {
+        for n in 1..=count {
+            store
+                .add_problem(n, &format!("Problem {n}"), "Arrays & Hashing", None, clock)
+                .unwrap();
+        }
+    }
+
+    // Every day at the clock's time of day, practices whatever is due and rates it with
+    // `rate(lc_number, day)`. Returns the days each problem was practiced on.
+    fn simulate(
+        store: &dyn ProblemStore,
+        scheduler: &dyn Scheduler,
+        clock: &FixedClock,
+        days: u32,
+        daily_limit: Option<usize>,
+        rate: impl Fn(u32, u32) -> Rating,
+    ) -> BTreeMap<u32, Vec<u32>> {
+        let mut practiced: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for day in 0..days {
+            let all_problems = store.problems().unwrap().rows;
+            let todays_problems =
+                get_todays_problems(&all_problems, scheduler, &utc(), daily_limit, clock);
+            for problem in todays_problems {
+                let rating = rate(problem.lc_number, day);
+                store
+                    .complete_problem(&problem, rating, None, None, scheduler, clock)
+                    .unwrap();
+                practiced.entry(problem.lc_number).or_default().push(day);
+            }
+            clock.advance(TimeDelta::days(1));
+        }
+        practiced
+    }
+
+    #[test]
+    fn good_reviews_spread_out_over_weeks() {
+        for store in stores() {
+            let clock = clock_at_start();
+            add_problems(store.as_ref(), 1, &clock);
+
+            let practiced = simulate(store.as_ref(), &Sm2, &clock, 70, None, |_, _| Rating::Good);
+            assert_eq!(practiced[&1], vec![0, 1, 7, 22, 60]);
+
+            let reviews = store.reviews().unwrap().rows;
+            assert_eq!(reviews.len(), 5);
+            assert_eq!(
+                reviews.last().unwrap().reviewed_at,
+                Utc.with_ymd_and_hms(2024, 4, 30, 9, 0, 0).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn again_brings_a_problem_back_the_next_day() {
+        for store in stores() {
+            let clock = clock_at_start();
+            add_problems(store.as_ref(), 1, &clock);
+
+            let practiced = simulate(store.as_ref(), &Sm2, &clock, 21, None, |_, day| {
+                if day == 7 {
+                    Rating::Again
+                } else {
+                    Rating::Good
+                }
+            });
+            assert_eq!(practiced[&1], vec![0, 1, 7, 8, 9, 15]);
+        }
+    }
+
+    #[test]
+    fn daily_limit_carries_the_backlog_over() {
+        for store in stores() {
+            let clock = clock_at_start();
+            add_problems(store.as_ref(), 5, &clock);
+
+            let practiced = simulate(store.as_ref(), &Sm2, &clock, 3, Some(2), |_, _| {
+                Rating::Good
+            });
+            let mut per_day = [0; 3];
+            for day in practiced.values().flatten() {
+                per_day[*day as usize] += 1;
+            }
+            assert_eq!(per_day, [2, 2, 2]);
+            // oldest due first, so every problem gets its first review within three days
+            assert_eq!(practiced.len(), 5);
+        }
+    }
+
+    #[test]
+    fn problems_due_tonight_show_up_in_the_morning() {
+        for store in stores() {
+            let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 1, 20, 0, 0).unwrap());
+            add_problems(store.as_ref(), 1, &clock);
+            let problem = store.problem_by_number(1).unwrap().unwrap();
+            store
+                .complete_problem(&problem, Rating::Good, None, None, &Sm2, &clock)
+                .unwrap();
+
+            // due at 8pm the next day, but listed from the start of that day
+            clock.advance(TimeDelta::hours(12));
+            let all_problems = store.problems().unwrap().rows;
+            let todays_problems = get_todays_problems(&all_problems, &Sm2, &utc(), None, &clock);
+            assert_eq!(todays_problems.len(), 1);
+        }
+    }
+
+    #[test]
+    fn heuristic_sample_changes_daily_not_hourly() {
+        for store in stores() {
+            let clock = clock_at_start();
+            add_problems(store.as_ref(), 30, &clock);
+            let heuristic = Heuristic {
+                sample_size: 5,
+                limit: 3,
+            };
+            let all_problems = store.problems().unwrap().rows;
+            let todays_numbers = |clock: &FixedClock| -> Vec<u32> {
+                get_todays_problems(&all_problems, &heuristic, &utc(), None, clock)
+                    .iter()
+                    .map(|problem| problem.lc_number)
+                    .collect()
+            };
+
+            let mut daily_picks = vec![];
+            for _ in 0..7 {
+                let morning = todays_numbers(&clock);
+                clock.advance(TimeDelta::hours(14));
+                assert_eq!(todays_numbers(&clock), morning);
+                clock.advance(TimeDelta::hours(10));
+                daily_picks.push(morning);
+            }
+            assert!(daily_picks.windows(2).any(|pair| pair[0] != pair[1]));
+        }
+    }
+
+    #[test]
+    fn difficulty_prior_only_scales_the_first_interval() {
+        for store in stores() {
+            let clock = clock_at_start();
+            let prior = DifficultyPrior {
+                inner: Box::new(Sm2),
+            };
+            for (n, difficulty) in [
+                (1, None),
+                (2, Some(Difficulty::Easy)),
+                (3, Some(Difficulty::Hard)),
+            ] {
+                store
+                    .add_problem(
+                        n,
+                        &format!("Problem {n}"),
+                        "Arrays & Hashing",
+                        difficulty,
+                        &clock,
+                    )
+                    .unwrap();
+            }
+
+            let intervals = |rating: Rating| -> Vec<u32> {
+                store
+                    .problems()
+                    .unwrap()
+                    .rows
+                    .iter()
+                    .map(|problem| {
+                        store
+                            .complete_problem(problem, rating, None, None, &prior, &clock)
+                            .unwrap()
+                            .interval
+                    })
+                    .collect()
+            };
+            // SM-2 gives an Easy rating 4 days, then 6 * 1.3 = 8 whatever the first interval was
+            let mut first = intervals(Rating::Easy);
+            first.sort();
+            assert_eq!(first, vec![2, 4, 6]);
+            assert_eq!(intervals(Rating::Easy), vec![8, 8, 8]);
+        }
+    }
+
+    #[test]
+    fn edits_and_deletes_match_between_backends() {
+        for store in stores() {
+            let clock = clock_at_start();
+            add_problems(store.as_ref(), 2, &clock);
+            let first = store.problem_by_number(1).unwrap().unwrap();
+            let second = store.problem_by_number(2).unwrap().unwrap();
+            assert!(store.problem_exists(2, "Something else").unwrap());
+            assert!(!store
+                .other_problem_exists(&second.id, 2, "Problem 2")
+                .unwrap());
+
+            store
+                .update_problem(&second.id, 3, "Renamed", "Trees", Some(Difficulty::Hard))
+                .unwrap();
+            let renamed = store.problem(&second.id).unwrap().unwrap();
+            assert_eq!(
+                (
+                    renamed.lc_number,
+                    renamed.problem_name.as_str(),
+                    renamed.lc_difficulty
+                ),
+                (3, "Renamed", Some(Difficulty::Hard))
+            );
+
+            let notes = "monotonic stack\niterate right to left";
+            store.update_notes(&first.id, Some(notes)).unwrap();
+            let first = store.problem(&first.id).unwrap().unwrap();
+            assert_eq!(first.notes.as_deref(), Some(notes));
+
+            for problem in [&first, &renamed] {
+                store
+                    .complete_problem(problem, Rating::Good, Some(20), Some("ok"), &Sm2, &clock)
+                    .unwrap();
+                clock.advance(TimeDelta::hours(1));
+            }
+            let day = utc().day_range(utc().today(clock.now()));
+            assert_eq!(store.reviews_between(day.0, day.1).unwrap().rows.len(), 2);
+
+            store.delete_problem(&first.id).unwrap();
+            assert!(store.problem(&first.id).unwrap().is_none());
+            assert!(store
+                .reviews_for_problem(&first.id)
+                .unwrap()
+                .rows
+                .is_empty());
+            assert_eq!(store.reviews().unwrap().rows.len(), 1);
+            assert_eq!(store.problems().unwrap().rows.len(), 1);
+        }
+    }
+
+    #[test]
+    fn solutions_keep_every_version() {
+        for store in stores() {
+            let clock = clock_at_start();
+            add_problems(store.as_ref(), 2, &clock);
+            let first = store.problem_by_number(1).unwrap().unwrap();
+            let second = store.problem_by_number(2).unwrap().unwrap();
+
+            for code in ["fn a() {}", "fn b() {}"] {
+                store.add_solution(&first, "rust", code, &clock).unwrap();
+                clock.advance(TimeDelta::days(30));
+            }
+            store
+                .add_solution(&second, "python", "def a(): pass", &clock)
+                .unwrap();
+
+            let versions: Vec<(u32, String)> = store
+                .solutions_for_problem(&first.id)
+                .unwrap()
+                .rows
+                .into_iter()
+                .map(|solution| (solution.version, solution.code))
+                .collect();
+            assert_eq!(
+                versions,
+                vec![(1, "fn a() {}".to_string()), (2, "fn b() {}".to_string())]
+            );
+
+            store.delete_problem(&first.id).unwrap();
+            assert!(store
+                .solutions_for_problem(&first.id)
+                .unwrap()
+                .rows
+                .is_empty());
+            assert_eq!(
+                store.solutions_for_problem(&second.id).unwrap().rows.len(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn fitted_weights_are_saved_and_replaced() {
+        for store in stores() {
+            let clock = clock_at_start();
+            assert_eq!(store.fsrs_weights().unwrap(), None);
+
+            let mut fitted = FittedWeights {
+                weights: DEFAULT_WEIGHTS.map(|weight| weight * 1.1),
+                review_count: 40,
+                fitted_at: clock.now(),
+            };
+            store.save_fsrs_weights(&fitted).unwrap();
+            assert_eq!(store.fsrs_weights().unwrap().as_ref(), Some(&fitted));
+
+            fitted.review_count = 50;
+            store.save_fsrs_weights(&fitted).unwrap();
+            assert_eq!(store.fsrs_weights().unwrap(), Some(fitted));
+        }
+    }
+
+    // A file with a changed, more recently practiced problem 1 and a new problem 2, whose
+    // reviews refer to their problem by id and by number respectively
+    const IMPORT_FILE: &str = r#"{
+        "problems": [
+            {"id": "file-1", "lc_number": 1, "problem_name": "Two Sum", "problem_type": "Arrays & Hashing",
+             "last_practiced": "2024-03-10T09:00:00Z", "times_practiced": 4, "interval": 9,
+             "next_due": "2024-03-19T09:00:00Z"},
+            {"id": "file-2", "lc_number": 2, "problem_name": "Add Two Numbers", "problem_type": "Linked List"}
+        ],
+        "reviews": [
+            {"id": "review-1", "problem_id": "file-1", "reviewed_at": "2024-03-10T09:00:00Z", "rating": "Good"},
+            {"id": "review-2", "lc_number": 2, "reviewed_at": "2024-03-01T10:00:00Z", "rating": "Hard"}
+        ]
+    }"#;
+
+    fn import_json(
+        store: &dyn ProblemStore,
+        contents: &str,
+        strategy: DuplicateStrategy,
+        dry_run: bool,
+        clock: &FixedClock,
+    ) -> Result<ImportSummary> {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("import.json");
+        fs::write(&path, contents).unwrap();
+        let categories = Config::default().categories;
+        import(
+            store,
+            &path,
+            Format::Json,
+            strategy,
+            &categories,
+            dry_run,
+            clock,
+        )
+    }
+
+    fn review_ids(store: &dyn ProblemStore, problem_id: &str) -> Vec<String> {
+        store
+            .reviews_for_problem(problem_id)
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|review| review.id)
+            .collect()
+    }
+
+    #[test]
+    fn import_handles_duplicates_by_strategy() {
+        for strategy in [
+            DuplicateStrategy::Skip,
+            DuplicateStrategy::Overwrite,
+            DuplicateStrategy::Merge,
+        ] {
+            for store in stores() {
+                let clock = clock_at_start();
+                add_problems(store.as_ref(), 1, &clock);
+                let existing = store.problem_by_number(1).unwrap().unwrap();
+
+                let summary =
+                    import_json(store.as_ref(), IMPORT_FILE, strategy, false, &clock).unwrap();
+                let actions: Vec<&ImportAction> =
+                    summary.problems.iter().map(|(action, _)| action).collect();
+                let duplicate = match strategy {
+                    DuplicateStrategy::Skip => ImportAction::Skip,
+                    DuplicateStrategy::Overwrite => ImportAction::Overwrite,
+                    DuplicateStrategy::Merge => ImportAction::Merge,
+                };
+                assert_eq!(actions, vec![&duplicate, &ImportAction::Add]);
+
+                // the existing problem keeps its id whatever happens to the rest of it
+                let first = store.problem_by_number(1).unwrap().unwrap();
+                assert_eq!(first.id, existing.id);
+                let (name, interval, reviews) = match strategy {
+                    DuplicateStrategy::Skip => ("Problem 1", 0, vec![]),
+                    DuplicateStrategy::Overwrite => ("Two Sum", 9, vec!["review-1"]),
+                    DuplicateStrategy::Merge => ("Problem 1", 9, vec!["review-1"]),
+                };
+                assert_eq!(
+                    (first.problem_name.as_str(), first.interval),
+                    (name, interval),
+                    "{strategy:?}"
+                );
+                assert_eq!(review_ids(store.as_ref(), &first.id), reviews);
+
+                let second = store.problem_by_number(2).unwrap().unwrap();
+                assert_eq!(second.id, "file-2");
+                // the file has no difficulty column, so it comes from the catalog
+                assert_eq!(second.lc_difficulty, Some(Difficulty::Medium));
+                assert_eq!(review_ids(store.as_ref(), &second.id), vec!["review-2"]);
+                assert_eq!(summary.reviews_added, reviews.len() + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn merge_keeps_the_more_recent_schedule() {
+        for store in stores() {
+            let clock = clock_at_start();
+            clock.advance(TimeDelta::days(30));
+            add_problems(store.as_ref(), 1, &clock);
+            let existing = store.problem_by_number(1).unwrap().unwrap();
+
+            import_json(
+                store.as_ref(),
+                IMPORT_FILE,
+                DuplicateStrategy::Merge,
+                false,
+                &clock,
+            )
+            .unwrap();
+            // practiced in the file before it was added here, so only the review is taken
+            let merged = store.problem_by_number(1).unwrap().unwrap();
+            assert_eq!(merged, existing);
+            assert_eq!(review_ids(store.as_ref(), &merged.id), vec!["review-1"]);
+        }
+    }
+
+    #[test]
+    fn import_gives_a_new_id_when_the_files_id_is_taken() {
+        for store in stores() {
+            let clock = clock_at_start();
+            add_problems(store.as_ref(), 1, &clock);
+            let existing = store.problem_by_number(1).unwrap().unwrap();
+            let file = format!(
+                r#"{{
+                    "problems": [{{"id": "{id}", "lc_number": 5, "problem_name": "Longest Palindromic Substring",
+                                   "problem_type": "2-D Dynamic Programming"}}],
+                    "reviews": [{{"problem_id": "{id}", "reviewed_at": "2024-03-01T10:00:00Z", "rating": "Easy"}}]
+                }}"#,
+                id = existing.id
+            );
+
+            import_json(
+                store.as_ref(),
+                &file,
+                DuplicateStrategy::Skip,
+                false,
+                &clock,
+            )
+            .unwrap();
+            let added = store.problem_by_number(5).unwrap().unwrap();
+            assert_ne!(added.id, existing.id);
+            // the review follows the problem it was for, not the id it had in the file
+            assert_eq!(review_ids(store.as_ref(), &added.id).len(), 1);
+            assert!(review_ids(store.as_ref(), &existing.id).is_empty());
+            assert_eq!(store.problem(&existing.id).unwrap().unwrap(), existing);
+        }
+    }
+
+    #[test]
+    fn dry_runs_and_failed_imports_change_nothing() {
+        for store in stores() {
+            let clock = clock_at_start();
+            add_problems(store.as_ref(), 1, &clock);
+            let problems = store.problems().unwrap().rows;
+
+            let summary = import_json(
+                store.as_ref(),
+                IMPORT_FILE,
+                DuplicateStrategy::Overwrite,
+                true,
+                &clock,
+            )
+            .unwrap();
+            assert_eq!(summary.problems.len(), 2);
+            assert_eq!(summary.reviews_added, 2);
+            assert_eq!(store.problems().unwrap().rows, problems);
+            assert!(store.reviews().unwrap().rows.is_empty());
+
+            let invalid = IMPORT_FILE.replace("Linked List", "Linked Lists");
+            let err = import_json(
+                store.as_ref(),
+                &invalid,
+                DuplicateStrategy::Overwrite,
+                false,
+                &clock,
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("unknown category"), "{err}");
+            assert_eq!(store.problems().unwrap().rows, problems);
+
+            let failed = store.atomically(&mut || {
+                store.add_problem(2, "Problem 2", "Arrays & Hashing", None, &clock)?;
+                Err(Error::validation("stop"))
+            });
+            assert!(failed.is_err());
+            assert_eq!(store.problems().unwrap().rows, problems);
+        }
+    }
+
+    #[test]
+    fn unreadable_rows_are_skipped_and_reported() {
+        let store = SqliteStore::in_memory().unwrap();
+        let clock = clock_at_start();
+        add_problems(&store, 3, &clock);
+        store
+            .connection()
+            .execute(
+                "UPDATE problems SET next_due = 'someday' WHERE lc_number = 2",
+                [],
+            )
+            .unwrap();
+
+        let problems = store.problems().unwrap();
+        let numbers: Vec<u32> = problems
+            .rows
+            .iter()
+            .map(|problem| problem.lc_number)
+            .collect();
+        assert_eq!(numbers, vec![1, 3]);
+        assert_eq!(problems.skipped.len(), 1);
+        let message = problems.skipped[0].to_string();
+        assert!(message.starts_with("Could not read problem "), "{message}");
+        assert!(message.contains("bad next_due"), "{message}");
+
+        // looking the row up on its own names it rather than passing for a missing problem
+        let corrupt = store.problem_by_number(2).unwrap_err().to_string();
+        assert_eq!(corrupt, message);
+        assert!(store.problem_by_number(3).unwrap().is_some());
+    }
+}
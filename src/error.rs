@@ -0,0 +1,83 @@
+use std::{fmt, io};
+
+// Everything that can go wrong in the library, each with a note on what was being done
+#[derive(Debug)]
+pub enum Error {
+    Database {
+        context: String,
+        source: rusqlite::Error,
+    },
+    // A stored or imported value that couldn't be read
+    Parse {
+        context: String,
+        message: String,
+    },
+    // Input that was read fine but isn't allowed, e.g. an unknown category
+    Validation(String),
+    Io {
+        context: String,
+        source: io::Error,
+    },
+    Config(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn parse(context: impl Into<String>, message: impl fmt::Display) -> Self {
+        Error::Parse {
+            context: context.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Error::Validation(message.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Database { context, source } => write!(f, "{context}: {source}"),
+            Error::Parse { context, message } => write!(f, "{context}: {message}"),
+            Error::Validation(message) => write!(f, "{message}"),
+            Error::Io { context, source } => write!(f, "{context}: {source}"),
+            Error::Config(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Database { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// Attaches what was being done to a database or io error, e.g.
+// `fs::create_dir_all(&dir).context(format!("creating {}", dir.display()))?`
+pub trait Context<T> {
+    fn context(self, context: impl Into<String>) -> Result<T>;
+}
+
+impl<T> Context<T> for std::result::Result<T, rusqlite::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Database {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl<T> Context<T> for std::result::Result<T, io::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Io {
+            context: context.into(),
+            source,
+        })
+    }
+}
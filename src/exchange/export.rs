@@ -0,0 +1,238 @@
+use crate::{
+    clock::Clock,
+    db::store::ProblemStore,
+    error::{Context, Error, Result},
+    exchange::{
+        reviews_csv_path, rfc3339, ExportFile, Format, ProblemRecord, ReviewRecord, FORMAT_VERSION,
+    },
+};
+use std::{collections::HashMap, fs::File, io, io::BufWriter, path::Path};
+
+pub struct ExportSummary {
+    pub problems: usize,
+    pub reviews: usize,
+    pub skipped: Vec<Error>, // rows that couldn't be read and were left out
+}
+
+// Writes every problem and review to `path`. For CSV the reviews are written to a second
+// file next to it, see reviews_csv_path.
+pub fn export(
+    store: &dyn ProblemStore,
+    path: &Path,
+    format: Format,
+    clock: &dyn Clock,
+) -> Result<ExportSummary> {
+    let problems = store.problems()?;
+    let reviews = store.reviews()?;
+    let mut skipped = problems.skipped;
+    skipped.extend(reviews.skipped);
+    let (problems, reviews) = (problems.rows, reviews.rows);
+
+    let lc_numbers: HashMap<&str, u32> = problems
+        .iter()
+        .map(|problem| (problem.id.as_str(), problem.lc_number))
+        .collect();
+
+    let problem_records: Vec<ProblemRecord> = problems.iter().map(ProblemRecord::from).collect();
+    let review_records: Vec<ReviewRecord> = reviews
+        .iter()
+        .map(|review| {
+            ReviewRecord::new(review, lc_numbers.get(review.problem_id.as_str()).copied())
+        })
+        .collect();
+
+    let summary = ExportSummary {
+        problems: problem_records.len(),
+        reviews: review_records.len(),
+        skipped,
+    };
+
+    match format {
+        Format::Json => {
+            let export_file = ExportFile {
+                version: FORMAT_VERSION,
+                exported_at: rfc3339(clock.now()),
+                problems: problem_records,
+                reviews: review_records,
+            };
+            let context = || format!("Could not write {}", path.display());
+            let writer = BufWriter::new(File::create(path).context(context())?);
+            serde_json::to_writer_pretty(writer, &export_file)
+                .map_err(io::Error::from)
+                .context(context())?;
+        }
+        Format::Csv => {
+            write_csv(path, &problem_records)?;
+            write_csv(&reviews_csv_path(path), &review_records)?;
+        }
+    }
+
+    Ok(summary)
+}
+
+fn write_csv<T: serde::Serialize>(path: &Path, records: &[T]) -> Result<()> {
+    let context = || format!("Could not write {}", path.display());
+    let mut writer = csv::Writer::from_writer(File::create(path).context(context())?);
+    for record in records {
+        writer
+            .serialize(record)
+            .map_err(io::Error::from)
+            .context(context())?;
+    }
+    writer.flush().context(context())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        clock::FixedClock,
+        config::Config,
+        db::{
+            models::{Difficulty, Rating},
+            store::{MemoryStore, SqliteStore},
+        },
+        exchange::import::{import, DuplicateStrategy, ImportAction},
+        scheduler::sm2::Sm2,
+    };
+    use chrono::{TimeDelta, TimeZone, Utc};
+    use std::{fs, path::PathBuf};
+    use tempfile::TempDir;
+
+    // Two problems with notes, a difficulty and a few reviews between them
+    fn practiced_store(clock: &FixedClock) -> SqliteStore {
+        let store = SqliteStore::in_memory().unwrap();
+        store
+            .add_problem(
+                42,
+                "Trapping Rain Water",
+                "Two Pointers",
+                Some(Difficulty::Hard),
+                clock,
+            )
+            .unwrap();
+        store
+            .add_problem(1, "Two Sum", "Arrays & Hashing", None, clock)
+            .unwrap();
+        let trapping = store.problem_by_number(42).unwrap().unwrap();
+        store
+            .update_notes(&trapping.id, Some("max height from each side\nthen sum"))
+            .unwrap();
+
+        for rating in [Rating::Hard, Rating::Good] {
+            for problem in store.problems().unwrap().rows {
+                store
+                    .complete_problem(
+                        &problem,
+                        rating,
+                        Some(900),
+                        Some("ok, \"quoted\""),
+                        &Sm2,
+                        clock,
+                    )
+                    .unwrap();
+            }
+            clock.advance(TimeDelta::days(1));
+        }
+        store
+    }
+
+    #[test]
+    fn exports_read_back_the_same() {
+        let whole_seconds = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let fractional = whole_seconds + TimeDelta::nanoseconds(123_456_789);
+        for (file_name, format, start) in [
+            ("backup.json", Format::Json, whole_seconds),
+            ("backup.csv", Format::Csv, whole_seconds),
+            ("backup.json", Format::Json, fractional),
+            ("backup.csv", Format::Csv, fractional),
+        ] {
+            let clock = FixedClock::new(start);
+            let store = practiced_store(&clock);
+            let dir = TempDir::new().unwrap();
+            let path = dir.path().join(file_name);
+
+            let summary = export(&store, &path, format, &clock).unwrap();
+            assert_eq!((summary.problems, summary.reviews), (2, 4));
+            assert!(summary.skipped.is_empty());
+
+            let categories = Config::default().categories;
+            let copy = MemoryStore::default();
+            let dry_run = import(
+                &copy,
+                &path,
+                format,
+                DuplicateStrategy::Skip,
+                &categories,
+                true,
+                &clock,
+            )
+            .unwrap();
+            assert!(dry_run
+                .problems
+                .iter()
+                .all(|(action, _)| *action == ImportAction::Add));
+            assert_eq!((dry_run.problems.len(), dry_run.reviews_added), (2, 4));
+            assert!(copy.problems().unwrap().rows.is_empty());
+
+            import(
+                &copy,
+                &path,
+                format,
+                DuplicateStrategy::Skip,
+                &categories,
+                false,
+                &clock,
+            )
+            .unwrap();
+            assert_eq!(
+                copy.problems().unwrap().rows,
+                store.problems().unwrap().rows
+            );
+            assert_eq!(copy.reviews().unwrap().rows, store.reviews().unwrap().rows);
+        }
+    }
+
+    #[test]
+    fn json_exports_are_stamped_with_the_clock() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap());
+        let store = practiced_store(&clock);
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("backup.json");
+
+        export(&store, &path, Format::Json, &clock).unwrap();
+        let file: ExportFile = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(file.version, FORMAT_VERSION);
+        assert_eq!(file.exported_at, "2024-03-03T09:00:00Z");
+    }
+
+    #[test]
+    fn csv_reviews_go_next_to_the_problems() {
+        assert_eq!(
+            reviews_csv_path(Path::new("exports/problems.csv")),
+            PathBuf::from("exports/problems.reviews.csv")
+        );
+        assert_eq!(
+            reviews_csv_path(Path::new("backup")),
+            PathBuf::from("backup.reviews.csv")
+        );
+
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap());
+        let store = practiced_store(&clock);
+        let dir = TempDir::new().unwrap();
+        export(
+            &store,
+            &dir.path().join("problems.csv"),
+            Format::Csv,
+            &clock,
+        )
+        .unwrap();
+
+        let mut files: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["problems.csv", "problems.reviews.csv"]);
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{
+    catalog,
+    clock::Clock,
+    db::{
+        models::{Difficulty, LCProblem, Rating, Review},
+        store::ProblemStore,
+    },
+    error::{Context, Error, Result},
+    exchange::{reviews_csv_path, Format},
+    validation::type_validator,
+};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    path::Path,
+    str::FromStr,
+};
+use uuid::Uuid;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DuplicateStrategy {
+    // Leave the existing problem alone and ignore the row and its reviews
+    Skip,
+    // Replace the existing problem's name, category and schedule with the row's
+    Overwrite,
+    // Keep the existing name and category (and difficulty and notes, when set), take
+    // whichever schedule was practiced more recently and add the row's reviews to the
+    // existing problem
+    Merge,
+}
+
+impl FromStr for DuplicateStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(DuplicateStrategy::Skip),
+            "overwrite" => Ok(DuplicateStrategy::Overwrite),
+            "merge" => Ok(DuplicateStrategy::Merge),
+            _ => Err(format!(
+                "Unknown duplicate strategy: {s} (expected skip, overwrite or merge)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ImportAction {
+    Add,
+    Skip,
+    Overwrite,
+    Merge,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    // What happened to each problem row, with a description of the problem
+    pub problems: Vec<(ImportAction, String)>,
+    pub reviews_added: usize,
+}
+
+// Only the number, name and category are required so hand-written files can be imported.
+// Everything else defaults to a freshly added problem.
+#[derive(Debug, Deserialize)]
+struct ProblemRow {
+    id: Option<String>,
+    lc_number: u32,
+    problem_name: String,
+    problem_type: String,
+    // None when the file has no such column, Some(None) when the difficulty is unknown
+    #[serde(default, deserialize_with = "present")]
+    lc_difficulty: Option<Option<String>>,
+    start_date: Option<String>,
+    last_practiced: Option<String>,
+    times_practiced: Option<u32>,
+    ease_factor: Option<f64>,
+    interval: Option<u32>,
+    repetitions: Option<u32>,
+    next_due: Option<String>,
+    stability: Option<f64>,
+    difficulty: Option<f64>,
+    last_rating: Option<String>,
+    notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewRow {
+    id: Option<String>,
+    problem_id: Option<String>,
+    lc_number: Option<u32>,
+    reviewed_at: String,
+    rating: String,
+    time_spent: Option<u32>,
+    notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportFile {
+    problems: Vec<ProblemRow>,
+    #[serde(default)]
+    reviews: Vec<ReviewRow>,
+}
+
+// Imports problems (and reviews, when present) all or nothing. Every row is validated
+// before anything is written. With dry_run the writes are rolled back, so the summary
+// describes what would have changed.
+pub fn import(
+    store: &dyn ProblemStore,
+    path: &Path,
+    format: Format,
+    strategy: DuplicateStrategy,
+    categories: &[String],
+    dry_run: bool,
+    clock: &dyn Clock,
+) -> Result<ImportSummary> {
+    let file = read_file(path, format)?;
+    let now = clock.now();
+
+    let mut problems = vec![];
+    let mut errors = vec![];
+    for (i, row) in file.problems.iter().enumerate() {
+        match to_problem(row, categories, now) {
+            Ok(problem) => problems.push(problem),
+            Err(err) => errors.push(format!("problem {}: {}", i + 1, err)),
+        }
+    }
+    let mut reviews = vec![];
+    for (i, row) in file.reviews.iter().enumerate() {
+        match to_review(row) {
+            Ok(review) => reviews.push(review),
+            Err(err) => errors.push(format!("review {}: {}", i + 1, err)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(Error::validation(format!(
+            "Nothing was imported:\n  {}",
+            errors.join("\n  ")
+        )));
+    }
+
+    let mut summary = ImportSummary::default();
+    store.atomically(&mut || {
+        summary = write_rows(store, &file, &problems, &reviews, strategy)?;
+        Ok(!dry_run)
+    })?;
+    Ok(summary)
+}
+
+fn write_rows(
+    store: &dyn ProblemStore,
+    file: &ImportFile,
+    problems: &[LCProblem],
+    reviews: &[Review],
+    strategy: DuplicateStrategy,
+) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    // Where each row's reviews should go, keyed by the file's problem id and number
+    let mut ids_by_file_id: HashMap<String, String> = HashMap::new();
+    let mut ids_by_number: HashMap<u32, String> = HashMap::new();
+
+    for (row, problem) in file.problems.iter().zip(problems) {
+        let problem = problem.clone();
+        let description = format!("{}: {}", problem.lc_number, problem.problem_name);
+        // A matching row that can't be read stops the import rather than being duplicated
+        let (action, problem_id) =
+            match store.matching_problem(problem.lc_number, &problem.problem_name)? {
+                None => {
+                    // The file's id may belong to a different problem in this database,
+                    // readable or not
+                    let mut problem = problem;
+                    if !matches!(store.problem(&problem.id), Ok(None)) {
+                        problem.id = Uuid::new_v4().to_string();
+                    }
+                    store.insert_problem(&problem)?;
+                    (ImportAction::Add, Some(problem.id.clone()))
+                }
+                Some(_) if strategy == DuplicateStrategy::Skip => (ImportAction::Skip, None),
+                Some(existing) if strategy == DuplicateStrategy::Overwrite => {
+                    store.replace_problem(&LCProblem {
+                        id: existing.id.clone(),
+                        ..problem
+                    })?;
+                    (ImportAction::Overwrite, Some(existing.id))
+                }
+                Some(existing) => {
+                    if problem.last_practiced > existing.last_practiced {
+                        store.replace_problem(&LCProblem {
+                            id: existing.id.clone(),
+                            lc_number: existing.lc_number,
+                            problem_name: existing.problem_name.clone(),
+                            problem_type: existing.problem_type.clone(),
+                            lc_difficulty: existing.lc_difficulty.or(problem.lc_difficulty),
+                            notes: existing.notes.clone().or(problem.notes),
+                            start_date: existing.start_date.min(problem.start_date),
+                            ..problem
+                        })?;
+                    }
+                    (ImportAction::Merge, Some(existing.id))
+                }
+            };
+
+        if let Some(problem_id) = problem_id {
+            if let Some(file_id) = &row.id {
+                ids_by_file_id.insert(file_id.clone(), problem_id.clone());
+            }
+            ids_by_number.insert(row.lc_number, problem_id);
+        }
+        summary.problems.push((action, description));
+    }
+
+    for (row, review) in file.reviews.iter().zip(reviews) {
+        let problem_id = row
+            .problem_id
+            .as_ref()
+            .and_then(|id| ids_by_file_id.get(id))
+            .or_else(|| row.lc_number.and_then(|n| ids_by_number.get(&n)));
+        if let Some(problem_id) = problem_id {
+            let review = Review {
+                problem_id: problem_id.clone(),
+                ..review.clone()
+            };
+            if store.insert_review(&review)? {
+                summary.reviews_added += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+fn read_file(path: &Path, format: Format) -> Result<ImportFile> {
+    match format {
+        Format::Json => {
+            let contents =
+                fs::read_to_string(path).context(format!("Could not read {}", path.display()))?;
+            serde_json::from_str(&contents)
+                .map_err(|err| Error::parse(format!("Invalid JSON in {}", path.display()), err))
+        }
+        Format::Csv => {
+            let problems = read_csv(path)?;
+            let reviews_path = reviews_csv_path(path);
+            let reviews = if reviews_path.exists() {
+                read_csv(&reviews_path)?
+            } else {
+                vec![]
+            };
+            Ok(ImportFile { problems, reviews })
+        }
+    }
+}
+
+fn read_csv<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let file = File::open(path).context(format!("Could not read {}", path.display()))?;
+    csv::Reader::from_reader(file)
+        .deserialize()
+        .collect::<Result<Vec<T>, _>>()
+        .map_err(|err| Error::parse(format!("Invalid CSV in {}", path.display()), err))
+}
+
+// Timestamps missing from the row are `now`, as if the problem had just been added
+fn to_problem(
+    row: &ProblemRow,
+    categories: &[String],
+    now: DateTime<Utc>,
+) -> Result<LCProblem, String> {
+    if row.problem_name.trim().is_empty() {
+        return Err("problem_name is empty".to_string());
+    }
+    if !type_validator(&row.problem_type, categories) {
+        return Err(format!("unknown category \"{}\"", row.problem_type));
+    }
+
+    let mut problem = LCProblem::new(
+        row.lc_number,
+        row.problem_name.trim(),
+        &row.problem_type,
+        now,
+    );
+    if let Some(id) = &row.id {
+        problem.id = id.clone();
+    }
+    // Files from before difficulty was tracked fall back to the catalog
+    problem.lc_difficulty = match &row.lc_difficulty {
+        Some(Some(difficulty)) if !difficulty.is_empty() => Some(difficulty.parse::<Difficulty>()?),
+        Some(_) => None,
+        None => catalog::lookup(row.lc_number).map(|entry| entry.difficulty),
+    };
+    if let Some(start_date) = &row.start_date {
+        problem.start_date = parse_date(start_date)?;
+    }
+    if let Some(last_practiced) = &row.last_practiced {
+        problem.last_practiced = parse_date(last_practiced)?;
+    }
+    problem.next_due = match &row.next_due {
+        Some(next_due) => parse_date(next_due)?,
+        None => problem.last_practiced,
+    };
+    problem.times_practiced = row.times_practiced.unwrap_or(problem.times_practiced);
+    problem.ease_factor = row.ease_factor.unwrap_or(problem.ease_factor);
+    problem.interval = row.interval.unwrap_or(problem.interval);
+    problem.repetitions = row.repetitions.unwrap_or(problem.repetitions);
+    problem.stability = row.stability.unwrap_or(problem.stability);
+    problem.difficulty = row.difficulty.unwrap_or(problem.difficulty);
+    problem.last_rating = match &row.last_rating {
+        Some(rating) if !rating.is_empty() => Some(rating.parse::<Rating>()?),
+        _ => None,
+    };
+    problem.notes = row.notes.clone().filter(|notes| !notes.is_empty());
+    Ok(problem)
+}
+
+// Tells a field that is there but empty apart from one that is missing
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+fn to_review(row: &ReviewRow) -> Result<Review, String> {
+    if row.problem_id.is_none() && row.lc_number.is_none() {
+        return Err("needs a problem_id or lc_number".to_string());
+    }
+    Ok(Review {
+        id: row.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string()),
+        problem_id: String::new(), // resolved once the problems are imported
+        reviewed_at: parse_date(&row.reviewed_at)?,
+        rating: row.rating.parse()?,
+        time_spent: row.time_spent,
+        notes: row.notes.clone().filter(|notes| !notes.is_empty()),
+    })
+}
+
+fn parse_date(date: &str) -> Result<DateTime<Utc>, String> {
+    date.parse()
+        .map_err(|_| format!("invalid timestamp \"{date}\", expected RFC 3339"))
+}
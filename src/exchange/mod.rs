@@ -0,0 +1,132 @@
+pub mod export;
+pub mod import;
+
+use crate::db::models::{LCProblem, Review};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+// Bumped whenever a column is renamed or removed, so old exports can still be read
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Format> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format: {s} (expected json or csv)")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportFile {
+    pub version: u32,
+    pub exported_at: String,
+    pub problems: Vec<ProblemRecord>,
+    pub reviews: Vec<ReviewRecord>,
+}
+
+// One row of the problems export. Timestamps are RFC 3339 in UTC.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProblemRecord {
+    pub id: String,
+    pub lc_number: u32,
+    pub problem_name: String,
+    pub problem_type: String,
+    pub lc_difficulty: Option<String>,
+    pub start_date: String,
+    pub last_practiced: String,
+    pub times_practiced: u32,
+    pub ease_factor: f64,
+    pub interval: u32,
+    pub repetitions: u32,
+    pub next_due: String,
+    pub stability: f64,
+    pub difficulty: f64,
+    pub last_rating: Option<String>,
+    pub notes: Option<String>,
+}
+
+// One row of the reviews export, with the problem's number for readability
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewRecord {
+    pub id: String,
+    pub problem_id: String,
+    pub lc_number: Option<u32>,
+    pub reviewed_at: String,
+    pub rating: String,
+    pub time_spent: Option<u32>,
+    pub notes: Option<String>,
+}
+
+impl From<&LCProblem> for ProblemRecord {
+    fn from(problem: &LCProblem) -> Self {
+        Self {
+            id: problem.id.clone(),
+            lc_number: problem.lc_number,
+            problem_name: problem.problem_name.clone(),
+            problem_type: problem.problem_type.clone(),
+            lc_difficulty: problem
+                .lc_difficulty
+                .map(|difficulty| difficulty.as_str().to_string()),
+            start_date: rfc3339(problem.start_date),
+            last_practiced: rfc3339(problem.last_practiced),
+            times_practiced: problem.times_practiced,
+            ease_factor: problem.ease_factor,
+            interval: problem.interval,
+            repetitions: problem.repetitions,
+            next_due: rfc3339(problem.next_due),
+            stability: problem.stability,
+            difficulty: problem.difficulty,
+            last_rating: problem
+                .last_rating
+                .map(|rating| rating.as_str().to_string()),
+            notes: problem.notes.clone(),
+        }
+    }
+}
+
+impl ReviewRecord {
+    pub fn new(review: &Review, lc_number: Option<u32>) -> Self {
+        Self {
+            id: review.id.clone(),
+            problem_id: review.problem_id.clone(),
+            lc_number,
+            reviewed_at: rfc3339(review.reviewed_at),
+            rating: review.rating.as_str().to_string(),
+            time_spent: review.time_spent,
+            notes: review.notes.clone(),
+        }
+    }
+}
+
+pub fn rfc3339(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+// CSV can only hold one table per file, so reviews go next to the problems file:
+// problems.csv -> problems.reviews.csv
+pub fn reviews_csv_path(path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(path.file_stem().unwrap_or_default());
+    file_name.push(".reviews.csv");
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,256 @@
+use crate::{calendar::Calendar, db::models::Review};
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use std::fmt::Write;
+
+// GitHub's contribution colors, from no reviews to the busiest days
+const SVG_COLORS: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+const SVG_CELL: usize = 10;
+const SVG_STEP: usize = 13; // cell plus gap
+const SVG_LEFT: usize = 30; // room for weekday labels
+const SVG_TOP: usize = 20; // room for month labels
+
+// Reviews per day over the past year, laid out in week columns starting on Sunday
+pub struct Heatmap {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    counts: Vec<u32>,
+    max: u32,
+}
+
+impl Heatmap {
+    // The Sunday starting the first column for a calendar ending on `today`
+    pub fn first_day(today: NaiveDate) -> NaiveDate {
+        let year_ago = today - Days::new(364);
+        year_ago - Days::new(year_ago.weekday().num_days_from_sunday() as u64)
+    }
+
+    // Half-open range of review timestamps the calendar covers
+    pub fn review_range(calendar: &Calendar, today: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            calendar.start_of_day(Heatmap::first_day(today)),
+            calendar.day_range(today).1,
+        )
+    }
+
+    pub fn new(reviews: &[Review], calendar: &Calendar, today: NaiveDate) -> Self {
+        let start = Heatmap::first_day(today);
+        let mut counts = vec![0; (today - start).num_days() as usize + 1];
+        for review in reviews {
+            let day = calendar.day_of(review.reviewed_at);
+            if day >= start && day <= today {
+                counts[(day - start).num_days() as usize] += 1;
+            }
+        }
+        let max = counts.iter().copied().max().unwrap_or(0);
+
+        Heatmap {
+            start,
+            end: today,
+            counts,
+            max,
+        }
+    }
+
+    pub fn weeks(&self) -> usize {
+        self.counts.len().div_ceil(7)
+    }
+
+    pub fn date(&self, week: usize, weekday: usize) -> NaiveDate {
+        self.start + Days::new((week * 7 + weekday) as u64)
+    }
+
+    // None for the days after `end` in the last column
+    pub fn count(&self, week: usize, weekday: usize) -> Option<u32> {
+        self.counts.get(week * 7 + weekday).copied()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    pub fn active_days(&self) -> usize {
+        self.counts.iter().filter(|count| **count > 0).count()
+    }
+
+    // Shade from 0 (no reviews) to 4, relative to the busiest day
+    pub fn level(&self, count: u32) -> usize {
+        if count == 0 {
+            0
+        } else {
+            (count * 4).div_ceil(self.max) as usize
+        }
+    }
+
+    // Month name for a column whose first day starts a new month
+    pub fn month_label(&self, week: usize) -> Option<String> {
+        let first = self.date(week, 0);
+        if week > 0 && first.month() != self.date(week - 1, 0).month() {
+            Some(first.format("%b").to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let width = SVG_LEFT + self.weeks() * SVG_STEP + 10;
+        let height = SVG_TOP + 7 * SVG_STEP + 30;
+        let mut svg = String::new();
+
+        // writing into a String can't fail
+        let _ = writeln!(
+            svg,
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" font-family="sans-serif" font-size="9" fill="#767676">"##
+        );
+        for (weekday, label) in [(1, "Mon"), (3, "Wed"), (5, "Fri")] {
+            let _ = writeln!(
+                svg,
+                r#"  <text x="0" y="{}">{label}</text>"#,
+                SVG_TOP + weekday * SVG_STEP + SVG_CELL - 1
+            );
+        }
+        for week in 0..self.weeks() {
+            let x = SVG_LEFT + week * SVG_STEP;
+            if let Some(month) = self.month_label(week) {
+                let _ = writeln!(svg, r#"  <text x="{x}" y="{}">{month}</text>"#, SVG_TOP - 6);
+            }
+            for weekday in 0..7 {
+                let Some(count) = self.count(week, weekday) else {
+                    break;
+                };
+                let _ = writeln!(
+                    svg,
+                    r#"  <rect x="{x}" y="{}" width="{SVG_CELL}" height="{SVG_CELL}" rx="2" fill="{}"><title>{}: {count} review{}</title></rect>"#,
+                    SVG_TOP + weekday * SVG_STEP,
+                    SVG_COLORS[self.level(count)],
+                    self.date(week, weekday).format("%Y-%m-%d"),
+                    if count == 1 { "" } else { "s" }
+                );
+            }
+        }
+
+        let legend_y = SVG_TOP + 7 * SVG_STEP + 8;
+        let _ = writeln!(
+            svg,
+            r#"  <text x="{SVG_LEFT}" y="{}">{} reviews on {} days</text>"#,
+            legend_y + SVG_CELL - 1,
+            self.total(),
+            self.active_days()
+        );
+        let legend_x = width - 10 - (SVG_COLORS.len() * SVG_STEP) - 60;
+        let _ = writeln!(
+            svg,
+            r#"  <text x="{legend_x}" y="{}">Less</text>"#,
+            legend_y + SVG_CELL - 1
+        );
+        for (i, color) in SVG_COLORS.iter().enumerate() {
+            let _ = writeln!(
+                svg,
+                r#"  <rect x="{}" y="{legend_y}" width="{SVG_CELL}" height="{SVG_CELL}" rx="2" fill="{color}"/>"#,
+                legend_x + 28 + i * SVG_STEP
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"  <text x="{}" y="{}">More</text>"#,
+            legend_x + 28 + SVG_COLORS.len() * SVG_STEP + 2,
+            legend_y + SVG_CELL - 1
+        );
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::Rating;
+    use chrono::TimeZone;
+    use chrono_tz::{Etc, Tz};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    fn reviews(moments: &[DateTime<Utc>]) -> Vec<Review> {
+        moments
+            .iter()
+            .enumerate()
+            .map(|(i, moment)| Review {
+                id: i.to_string(),
+                problem_id: "p".to_string(),
+                reviewed_at: *moment,
+                rating: Rating::Good,
+                time_spent: None,
+                notes: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_first_column_starts_on_the_sunday_a_year_back() {
+        // 2024-03-10 is a Sunday, 364 days earlier is too
+        assert_eq!(Heatmap::first_day(date(2024, 3, 10)), date(2023, 3, 12));
+        assert_eq!(Heatmap::first_day(date(2024, 3, 13)), date(2023, 3, 12));
+    }
+
+    #[test]
+    fn reviews_before_the_day_starts_count_for_the_day_before() {
+        let calendar = Calendar::new(Some(Tz::UTC), 4);
+        let today = date(2024, 3, 10);
+        let heatmap = Heatmap::new(
+            &reviews(&[
+                at(2023, 3, 12, 3), // still 2023-03-11, before the first column
+                at(2023, 3, 12, 4),
+                at(2024, 3, 10, 2), // still 2024-03-09
+                at(2024, 3, 10, 5),
+                at(2024, 3, 11, 3), // still today
+                at(2024, 3, 11, 5), // tomorrow
+            ]),
+            &calendar,
+            today,
+        );
+
+        assert_eq!((heatmap.start, heatmap.end), (date(2023, 3, 12), today));
+        assert_eq!(heatmap.weeks(), 53);
+        assert_eq!(heatmap.count(0, 0), Some(1));
+        assert_eq!(heatmap.count(51, 6), Some(1));
+        assert_eq!(heatmap.count(52, 0), Some(2));
+        assert_eq!(heatmap.count(52, 1), None);
+        assert_eq!((heatmap.total(), heatmap.active_days()), (4, 3));
+        assert_eq!(
+            (heatmap.level(0), heatmap.level(1), heatmap.level(2)),
+            (0, 2, 4)
+        );
+        assert_eq!(
+            Heatmap::review_range(&calendar, today),
+            (at(2023, 3, 12, 4), at(2024, 3, 11, 4))
+        );
+    }
+
+    #[test]
+    fn days_follow_the_calendars_time_zone() {
+        // UTC+2, so 23:00 UTC is already the next day
+        let calendar = Calendar::new(Some(Etc::GMTMinus2), 0);
+        let heatmap = Heatmap::new(
+            &reviews(&[at(2024, 3, 9, 21), at(2024, 3, 9, 23)]),
+            &calendar,
+            date(2024, 3, 10),
+        );
+        assert_eq!(heatmap.count(51, 6), Some(1));
+        assert_eq!(heatmap.count(52, 0), Some(1));
+    }
+
+    #[test]
+    fn months_are_labelled_on_the_column_they_start() {
+        let heatmap = Heatmap::new(&[], &Calendar::new(Some(Tz::UTC), 0), date(2024, 3, 10));
+        // columns start 2023-03-12, 19, 26 and then 2023-04-02
+        assert_eq!(heatmap.month_label(0), None);
+        assert_eq!(heatmap.month_label(2), None);
+        assert_eq!(heatmap.month_label(3), Some("Apr".to_string()));
+        assert!(heatmap.to_svg().contains("0 reviews on 0 days"));
+    }
+}
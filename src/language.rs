@@ -0,0 +1,77 @@
+use serde::Deserialize;
+use std::{path::Path, str::FromStr};
+
+// Languages solutions are commonly written in. Solutions store the name as text, so
+// anything else can still be saved, it just isn't recognized from the file extension.
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Rust,
+    Python,
+    Cpp,
+    C,
+    Java,
+    Go,
+    JavaScript,
+    TypeScript,
+}
+
+impl Language {
+    pub const ALL: [Language; 8] = [
+        Language::Rust,
+        Language::Python,
+        Language::Cpp,
+        Language::C,
+        Language::Java,
+        Language::Go,
+        Language::JavaScript,
+        Language::TypeScript,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::Cpp => "cpp",
+            Language::C => "c",
+            Language::Java => "java",
+            Language::Go => "go",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Language::Rust => "rs",
+            Language::Python => "py",
+            Language::Cpp => "cpp",
+            Language::C => "c",
+            Language::Java => "java",
+            Language::Go => "go",
+            Language::JavaScript => "js",
+            Language::TypeScript => "ts",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Language> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+// Accepts the name or the file extension
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        match s.as_str() {
+            "c++" | "cc" | "cxx" | "hpp" => Ok(Language::Cpp),
+            "h" => Ok(Language::C),
+            _ => Language::ALL
+                .into_iter()
+                .find(|language| language.as_str() == s || language.extension() == s)
+                .ok_or_else(|| format!("Unknown language: {s}")),
+        }
+    }
+}
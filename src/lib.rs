@@ -0,0 +1,29 @@
+// Spaced repetition for LeetCode practice: problem storage, schedulers and practice
+// statistics. The terminal UI is behind the `tui` feature (on by default), so headless
+// tools can depend on the rest without pulling in ratatui, crossterm and syntect.
+
+pub mod calendar;
+pub mod catalog;
+pub mod clock;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod exchange;
+pub mod heatmap;
+pub mod language;
+pub mod scaffold;
+pub mod scheduler;
+pub mod session;
+pub mod stats;
+pub mod streak;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod utils;
+pub mod validation;
+
+pub use calendar::Calendar;
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use config::Config;
+pub use db::models::{LCProblem, Rating, Review, Solution};
+pub use error::{Error, Result};
+pub use scheduler::{Scheduler, SchedulerKind};
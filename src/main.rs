@@ -1,19 +1,96 @@
-mod db;
-mod tui;
-mod utils;
+mod cli;
 
-use db::db::{get_connection, init_db};
+use crate::cli::{Cli, Command};
+use clap::Parser;
+use rusty_lcurve::{
+    clock::{Clock, SystemClock},
+    config::{legacy_db_path, resolve_db_path, Config},
+    db::store::SqliteStore,
+    error::Result,
+    scheduler::{self, fsrs, SchedulerKind},
+};
+use std::{process, rc::Rc};
 
-use crate::tui::tui::App;
-use std::error::Error;
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {err}");
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let cli = Cli::parse();
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let db_path = "rusty_l_db.db";
-    if let Err(err) = init_db(db_path) {
-        println!("Error initializing database: {:?}", err);
+    let config = Config::load()?;
+    #[cfg(feature = "tui")]
+    if let Some(Command::Demo) = cli.command {
+        return demo(config);
     }
 
-    let db_connection = get_connection(db_path).unwrap();
-    let _rusty_lcurve_tui = App::start_ui(db_connection);
+    let default_db_path = cli.db.is_none() && config.db_path.is_none();
+    let db_path = resolve_db_path(cli.db, &config)?;
+    if default_db_path && !db_path.exists() && legacy_db_path().exists() {
+        eprintln!(
+            "Note: found {} in the current directory, but the database is now read from {}. Move it there or pass --db {}.",
+            legacy_db_path().display(),
+            db_path.display(),
+            legacy_db_path().display()
+        );
+    }
+    let store = SqliteStore::open(&db_path)?;
+
+    let clock: Rc<dyn Clock> = Rc::new(SystemClock);
+    // Saved weights are reused until the review log has changed enough, `optimize` refits
+    // them on demand
+    let fsrs_weights = if config.scheduler.algorithm == SchedulerKind::Fsrs
+        && !matches!(cli.command, Some(Command::Optimize))
+    {
+        fsrs::current_weights(&store, clock.as_ref())?
+    } else {
+        fsrs::DEFAULT_WEIGHTS
+    };
+    let scheduler = scheduler::build(&config.scheduler, fsrs_weights);
+
+    match cli.command {
+        #[cfg(feature = "tui")]
+        None | Some(Command::Tui) => {
+            rusty_lcurve::tui::tui::App::start_ui(Box::new(store), scheduler, config, clock)?
+        }
+        #[cfg(not(feature = "tui"))]
+        None | Some(Command::Tui | Command::Demo) => {
+            return Err(rusty_lcurve::Error::validation(
+                "This build has no TUI, rebuild with the tui feature or pass a command",
+            ))
+        }
+        Some(command) => cli::run(command, &store, scheduler.as_ref(), &config, clock.as_ref())?,
+    }
     Ok(())
 }
+
+// Runs the TUI on the Blind 75 in an in-memory store, so nothing touches the database
+#[cfg(feature = "tui")]
+fn demo(config: Config) -> Result<()> {
+    use rusty_lcurve::{
+        catalog::{catalog, ProblemList},
+        db::store::{MemoryStore, ProblemStore},
+        validation::type_validator,
+    };
+
+    let clock: Rc<dyn Clock> = Rc::new(SystemClock);
+    let store = MemoryStore::default();
+    for entry in catalog().iter().filter(|entry| {
+        entry.in_list(ProblemList::Blind75)
+            && type_validator(&entry.problem_type, &config.categories)
+    }) {
+        store.add_problem(
+            entry.lc_number,
+            &entry.problem_name,
+            &entry.problem_type,
+            Some(entry.difficulty),
+            clock.as_ref(),
+        )?;
+    }
+
+    let scheduler = scheduler::build(&config.scheduler, fsrs::DEFAULT_WEIGHTS);
+    rusty_lcurve::tui::tui::App::start_ui(Box::new(store), scheduler, config, clock)
+}
@@ -0,0 +1,189 @@
+use crate::{
+    db::models::LCProblem,
+    error::{Context, Result},
+    language::Language,
+};
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+// Scratch file name for a problem, e.g. 0042_trapping_rain_water.rs
+pub fn file_name(problem: &LCProblem, language: Language) -> String {
+    let mut slug = String::new();
+    for c in problem.problem_name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_end_matches('_');
+    if slug.is_empty() {
+        format!("{:04}.{}", problem.lc_number, language.extension())
+    } else {
+        format!("{:04}_{slug}.{}", problem.lc_number, language.extension())
+    }
+}
+
+// A header comment describing the problem followed by the language's starting template
+pub fn scaffold(problem: &LCProblem, language: Language) -> String {
+    let comment = match language {
+        Language::Python => "#",
+        _ => "//",
+    };
+    let mut header = vec![
+        format!("{}. {}", problem.lc_number, problem.problem_name),
+        format!("Category: {}", problem.problem_type),
+    ];
+    if let Some(difficulty) = problem.lc_difficulty {
+        header.push(format!("Difficulty: {difficulty}"));
+    }
+    if let Some(notes) = problem
+        .notes
+        .as_deref()
+        .filter(|notes| !notes.trim().is_empty())
+    {
+        header.push(String::new());
+        header.push("Notes:".to_string());
+        header.extend(notes.lines().map(str::to_string));
+    }
+
+    let mut scaffold = String::new();
+    for line in header {
+        if line.is_empty() {
+            scaffold.push_str(comment);
+        } else {
+            scaffold.push_str(&format!("{comment} {line}"));
+        }
+        scaffold.push('\n');
+    }
+    scaffold.push('\n');
+    scaffold.push_str(template(language));
+    scaffold
+}
+
+fn template(language: Language) -> &'static str {
+    match language {
+        Language::Rust => "struct Solution;\n\nimpl Solution {\n}\n\nfn main() {\n}\n",
+        Language::Python => {
+            "class Solution:\n    pass\n\n\nif __name__ == \"__main__\":\n    pass\n"
+        }
+        Language::Cpp => {
+            "#include <bits/stdc++.h>\nusing namespace std;\n\nclass Solution {\npublic:\n};\n\nint main() {\n}\n"
+        }
+        Language::C => "#include <stdio.h>\n#include <stdlib.h>\n\nint main(void) {\n}\n",
+        Language::Java => {
+            "import java.util.*;\n\nclass Solution {\n    public static void main(String[] args) {\n    }\n}\n"
+        }
+        Language::Go => "package main\n\nfunc main() {\n}\n",
+        Language::JavaScript => "/**\n */\nfunction solve() {\n}\n",
+        Language::TypeScript => "function solve(): void {\n}\n",
+    }
+}
+
+// Writes the scaffold into `dir`, creating it if needed, and returns the file's path. An
+// existing scratch file is left alone so reopening a problem picks up the last attempt.
+pub fn create_scratch_file(dir: &Path, problem: &LCProblem, language: Language) -> Result<PathBuf> {
+    fs::create_dir_all(dir).context(format!("Could not create {}", dir.display()))?;
+    let path = dir.join(file_name(problem, language));
+    match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(mut file) => file
+            .write_all(scaffold(problem, language).as_bytes())
+            .context(format!("Could not write {}", path.display()))?,
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+        Err(err) => return Err(err).context(format!("Could not create {}", path.display())),
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::Difficulty;
+    use chrono::{TimeZone, Utc};
+    use tempfile::TempDir;
+
+    fn problem(lc_number: u32, problem_name: &str) -> LCProblem {
+        LCProblem::new(
+            lc_number,
+            problem_name,
+            "Two Pointers",
+            Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn file_names_are_the_padded_number_and_a_slug() {
+        assert_eq!(
+            file_name(&problem(42, "Trapping Rain Water"), Language::Rust),
+            "0042_trapping_rain_water.rs"
+        );
+        assert_eq!(
+            file_name(
+                &problem(3, "Longest Substring (No Repeats!)"),
+                Language::Python
+            ),
+            "0003_longest_substring_no_repeats.py"
+        );
+        assert_eq!(file_name(&problem(7, " ?! "), Language::Go), "0007.go");
+    }
+
+    #[test]
+    fn python_scaffolds_use_hash_comments() {
+        let problem = LCProblem {
+            lc_difficulty: Some(Difficulty::Hard),
+            notes: Some("Two pointers from both ends\nkeep the max heights".to_string()),
+            ..problem(42, "Trapping Rain Water")
+        };
+        assert_eq!(
+            scaffold(&problem, Language::Python),
+            "# 42. Trapping Rain Water\n\
+             # Category: Two Pointers\n\
+             # Difficulty: Hard\n\
+             #\n\
+             # Notes:\n\
+             # Two pointers from both ends\n\
+             # keep the max heights\n\
+             \n\
+             class Solution:\n    pass\n\n\nif __name__ == \"__main__\":\n    pass\n"
+        );
+    }
+
+    #[test]
+    fn unknown_difficulty_and_blank_notes_are_left_out() {
+        let problem = LCProblem {
+            notes: Some("  ".to_string()),
+            ..problem(42, "Trapping Rain Water")
+        };
+        assert_eq!(
+            scaffold(&problem, Language::Rust),
+            "// 42. Trapping Rain Water\n\
+             // Category: Two Pointers\n\
+             \n\
+             struct Solution;\n\nimpl Solution {\n}\n\nfn main() {\n}\n"
+        );
+    }
+
+    #[test]
+    fn scratch_files_are_never_overwritten() {
+        let dir = TempDir::new().unwrap();
+        let scratch = dir.path().join("scratch");
+        let problem = problem(42, "Trapping Rain Water");
+
+        let path = create_scratch_file(&scratch, &problem, Language::Rust).unwrap();
+        assert_eq!(path, scratch.join("0042_trapping_rain_water.rs"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            scaffold(&problem, Language::Rust)
+        );
+
+        fs::write(&path, "my attempt").unwrap();
+        assert_eq!(
+            create_scratch_file(&scratch, &problem, Language::Rust).unwrap(),
+            path
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "my attempt");
+    }
+}
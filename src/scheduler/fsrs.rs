@@ -0,0 +1,475 @@
+use crate::{
+    calendar::Calendar,
+    clock::Clock,
+    db::{
+        models::{LCProblem, Rating, Review},
+        store::ProblemStore,
+    },
+    error::Result,
+    scheduler::{due_problems, Scheduler},
+};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+// FSRS-4.5, see https://github.com/open-spaced-repetition/fsrs4anki/wiki/The-Algorithm
+pub const DEFAULT_WEIGHTS: [f64; 17] = [
+    0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461, 2.1072,
+    0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+];
+pub const DEFAULT_DESIRED_RETENTION: f64 = 0.9;
+
+const DECAY: f64 = -0.5;
+const FACTOR: f64 = 19.0 / 81.0;
+const MIN_STABILITY: f64 = 0.01;
+const MAX_INTERVAL: u32 = 36500;
+
+// Lower and upper bounds for each weight while optimizing, taken from fsrs-rs
+const WEIGHT_BOUNDS: [(f64, f64); 17] = [
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (1.0, 10.0),
+    (0.1, 4.0),
+    (0.1, 4.0),
+    (0.0, 0.75),
+    (0.0, 4.5),
+    (0.0, 0.8),
+    (0.01, 3.5),
+    (0.1, 5.0),
+    (0.01, 0.25),
+    (0.01, 0.9),
+    (0.01, 4.0),
+    (0.0, 1.0),
+    (1.0, 6.0),
+];
+const MIN_REVIEWS_TO_OPTIMIZE: usize = 32;
+const OPTIMIZER_ITERATIONS: usize = 200;
+const LEARNING_RATE: f64 = 0.02;
+// Fraction the review log has to grow (or shrink) by before the weights are refitted
+const REFIT_CHANGE: f64 = 0.25;
+
+// One problem's reviews in chronological order
+pub type ReviewHistory = Vec<(DateTime<Utc>, Rating)>;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MemoryState {
+    pub stability: f64,
+    pub difficulty: f64,
+}
+
+// Weights fitted to the review log, saved so the fit only reruns once the log has changed
+#[derive(Debug, PartialEq, Clone)]
+pub struct FittedWeights {
+    pub weights: [f64; 17], // the defaults when the log didn't improve on them
+    pub review_count: u32,  // size of the review log they were fitted to
+    pub fitted_at: DateTime<Utc>,
+}
+
+pub struct Fsrs {
+    pub weights: [f64; 17],
+    pub desired_retention: f64,
+}
+
+impl Default for Fsrs {
+    fn default() -> Self {
+        Self {
+            weights: DEFAULT_WEIGHTS,
+            desired_retention: DEFAULT_DESIRED_RETENTION,
+        }
+    }
+}
+
+impl Scheduler for Fsrs {
+    fn name(&self) -> &'static str {
+        "fsrs"
+    }
+
+    fn todays_problems(
+        &self,
+        all_problems: &[LCProblem],
+        now: DateTime<Utc>,
+        calendar: &Calendar,
+    ) -> Vec<LCProblem> {
+        due_problems(all_problems, now, calendar)
+    }
+
+    fn review(&self, problem: &mut LCProblem, rating: Rating, now: DateTime<Utc>) {
+        let elapsed_days = elapsed_days(problem.last_practiced, now);
+        let state = self.next_state(memory_state(problem), elapsed_days, rating);
+
+        problem.stability = state.stability;
+        problem.difficulty = state.difficulty;
+        problem.interval = self.next_interval(state.stability);
+        problem.last_practiced = now;
+        problem.times_practiced += 1;
+        problem.next_due = now + Duration::days(problem.interval as i64);
+        problem.last_rating = Some(rating);
+    }
+}
+
+impl Fsrs {
+    pub fn next_state(
+        &self,
+        state: Option<MemoryState>,
+        elapsed_days: f64,
+        rating: Rating,
+    ) -> MemoryState {
+        let w = &self.weights;
+        let g = grade(rating);
+
+        let Some(state) = state else {
+            return MemoryState {
+                stability: w[g as usize - 1].max(MIN_STABILITY),
+                difficulty: self.initial_difficulty(g),
+            };
+        };
+
+        let r = retrievability(elapsed_days, state.stability);
+        let stability = if rating == Rating::Again {
+            w[11]
+                * state.difficulty.powf(-w[12])
+                * ((state.stability + 1.0).powf(w[13]) - 1.0)
+                * (w[14] * (1.0 - r)).exp()
+        } else {
+            let hard_penalty = if rating == Rating::Hard { w[15] } else { 1.0 };
+            let easy_bonus = if rating == Rating::Easy { w[16] } else { 1.0 };
+            state.stability
+                * (w[8].exp()
+                    * (11.0 - state.difficulty)
+                    * state.stability.powf(-w[9])
+                    * ((w[10] * (1.0 - r)).exp() - 1.0)
+                    * hard_penalty
+                    * easy_bonus
+                    + 1.0)
+        };
+
+        // Difficulty moves with the grade and reverts towards the initial difficulty of an Easy
+        let difficulty = state.difficulty - w[6] * (g - 3.0);
+        let difficulty = w[7] * self.initial_difficulty(4.0) + (1.0 - w[7]) * difficulty;
+
+        MemoryState {
+            stability: stability.max(MIN_STABILITY),
+            difficulty: difficulty.clamp(1.0, 10.0),
+        }
+    }
+
+    // Days until retrievability drops to the desired retention
+    pub fn next_interval(&self, stability: f64) -> u32 {
+        let interval = stability / FACTOR * (self.desired_retention.powf(1.0 / DECAY) - 1.0);
+        (interval.round() as u32).clamp(1, MAX_INTERVAL)
+    }
+
+    fn initial_difficulty(&self, g: f64) -> f64 {
+        (self.weights[4] - (g - 3.0) * self.weights[5]).clamp(1.0, 10.0)
+    }
+}
+
+// Probability of recalling a problem `elapsed_days` after the last review
+pub fn retrievability(elapsed_days: f64, stability: f64) -> f64 {
+    (1.0 + FACTOR * elapsed_days / stability).powf(DECAY)
+}
+
+// Problems that haven't been reviewed under FSRS yet start from the initial stability
+pub fn memory_state(problem: &LCProblem) -> Option<MemoryState> {
+    if problem.stability > 0.0 {
+        Some(MemoryState {
+            stability: problem.stability,
+            difficulty: problem.difficulty,
+        })
+    } else {
+        None
+    }
+}
+
+fn grade(rating: Rating) -> f64 {
+    match rating {
+        Rating::Again => 1.0,
+        Rating::Hard => 2.0,
+        Rating::Good => 3.0,
+        Rating::Easy => 4.0,
+    }
+}
+
+fn elapsed_days(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to.signed_duration_since(from).num_seconds() as f64 / 86_400.0).max(0.0)
+}
+
+// The saved weights, refitted first if the review log has changed enough since they were
+// fitted. Weights that can't be read are refitted rather than failing every command.
+pub fn current_weights(store: &dyn ProblemStore, clock: &dyn Clock) -> Result<[f64; 17]> {
+    let fitted = store.fsrs_weights().ok().flatten();
+    if needs_refit(fitted.as_ref(), store.review_count()?) {
+        return Ok(refit(store, clock)?.weights);
+    }
+    Ok(fitted.map_or(DEFAULT_WEIGHTS, |fitted| fitted.weights))
+}
+
+// Fits the weights to the whole review log and saves them
+pub fn refit(store: &dyn ProblemStore, clock: &dyn Clock) -> Result<FittedWeights> {
+    let reviews = store.reviews()?;
+    let fitted = FittedWeights {
+        weights: optimize_weights(&review_histories(&reviews.rows)).unwrap_or(DEFAULT_WEIGHTS),
+        // unreadable reviews count too, so they don't trigger a refit on every start
+        review_count: (reviews.rows.len() + reviews.skipped.len()) as u32,
+        fitted_at: clock.now(),
+    };
+    store.save_fsrs_weights(&fitted)?;
+    Ok(fitted)
+}
+
+pub fn needs_refit(fitted: Option<&FittedWeights>, review_count: u32) -> bool {
+    match fitted {
+        None => review_count as usize >= MIN_REVIEWS_TO_OPTIMIZE,
+        Some(fitted) => {
+            review_count.abs_diff(fitted.review_count) as f64
+                >= fitted.review_count as f64 * REFIT_CHANGE
+                && review_count as usize >= MIN_REVIEWS_TO_OPTIMIZE
+        }
+    }
+}
+
+// Fits FSRS weights to a review history by minimizing the log loss of the predicted
+// recall probability against whether each review was actually recalled (anything but Again).
+// Returns None when there isn't enough history to improve on the defaults.
+pub fn optimize_weights(histories: &[ReviewHistory]) -> Option<[f64; 17]> {
+    let n_reviews: usize = histories
+        .iter()
+        .map(|history| history.len().saturating_sub(1))
+        .sum();
+    if n_reviews < MIN_REVIEWS_TO_OPTIMIZE {
+        return None;
+    }
+
+    // Adam with central finite-difference gradients; there are only 17 parameters
+    let mut weights = DEFAULT_WEIGHTS;
+    let mut m = [0.0; 17];
+    let mut v = [0.0; 17];
+    let (beta1, beta2, epsilon) = (0.9, 0.999, 1e-8);
+
+    for t in 1..=OPTIMIZER_ITERATIONS {
+        for i in 0..weights.len() {
+            let h = 1e-4 * weights[i].abs().max(1e-2);
+            let mut plus = weights;
+            let mut minus = weights;
+            plus[i] += h;
+            minus[i] -= h;
+            let gradient = (log_loss(&plus, histories) - log_loss(&minus, histories)) / (2.0 * h);
+
+            m[i] = beta1 * m[i] + (1.0 - beta1) * gradient;
+            v[i] = beta2 * v[i] + (1.0 - beta2) * gradient * gradient;
+            let m_hat = m[i] / (1.0 - beta1.powi(t as i32));
+            let v_hat = v[i] / (1.0 - beta2.powi(t as i32));
+
+            // Scale the step to the weight so w[3] ~ 14 and w[7] ~ 0.03 both move sensibly
+            let step = LEARNING_RATE * weights[i].abs().max(0.1) * m_hat / (v_hat.sqrt() + epsilon);
+            let (lower, upper) = WEIGHT_BOUNDS[i];
+            weights[i] = (weights[i] - step).clamp(lower, upper);
+        }
+    }
+
+    if log_loss(&weights, histories) < log_loss(&DEFAULT_WEIGHTS, histories) {
+        Some(weights)
+    } else {
+        None
+    }
+}
+
+// Groups the review log by problem, keeping each problem's reviews in chronological order
+pub fn review_histories(reviews: &[Review]) -> Vec<ReviewHistory> {
+    let mut histories: HashMap<&str, ReviewHistory> = HashMap::new();
+    for review in reviews {
+        histories
+            .entry(review.problem_id.as_str())
+            .or_default()
+            .push((review.reviewed_at, review.rating));
+    }
+
+    histories
+        .into_values()
+        .map(|mut history| {
+            history.sort_by_key(|(reviewed_at, _)| *reviewed_at);
+            history
+        })
+        .collect()
+}
+
+pub fn log_loss(weights: &[f64; 17], histories: &[ReviewHistory]) -> f64 {
+    let fsrs = Fsrs {
+        weights: *weights,
+        desired_retention: DEFAULT_DESIRED_RETENTION,
+    };
+    let mut total = 0.0;
+    let mut count = 0;
+
+    for history in histories {
+        let mut state = None;
+        let mut last_review: Option<DateTime<Utc>> = None;
+
+        for (reviewed_at, rating) in history {
+            let elapsed = last_review.map_or(0.0, |last| elapsed_days(last, *reviewed_at));
+            if let Some(MemoryState { stability, .. }) = state {
+                let r = retrievability(elapsed, stability).clamp(1e-6, 1.0 - 1e-6);
+                let recalled = *rating != Rating::Again;
+                total -= if recalled { r.ln() } else { (1.0 - r).ln() };
+                count += 1;
+            }
+            state = Some(fsrs.next_state(state, elapsed, *rating));
+            last_review = Some(*reviewed_at);
+        }
+    }
+
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clock::FixedClock, db::store::MemoryStore};
+    use chrono::{TimeDelta, TimeZone};
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn first_review_uses_the_initial_weights() {
+        let fsrs = Fsrs::default();
+        for (rating, stability, difficulty) in [
+            (Rating::Again, 0.4872, 7.6214),
+            (Rating::Hard, 1.4003, 6.3916),
+            (Rating::Good, 3.7145, 5.1618),
+            (Rating::Easy, 13.8206, 3.932),
+        ] {
+            let state = fsrs.next_state(None, 0.0, rating);
+            assert_close(state.stability, stability);
+            assert_close(state.difficulty, difficulty);
+        }
+    }
+
+    #[test]
+    fn stability_is_the_interval_at_90_percent_retention() {
+        assert_close(retrievability(3.7145, 3.7145), 0.9);
+        assert_close(retrievability(0.0, 3.7145), 1.0);
+        let fsrs = Fsrs::default();
+        assert_eq!(fsrs.next_interval(3.7145), 4);
+        assert_eq!(fsrs.next_interval(13.8206), 14);
+        let relaxed = Fsrs {
+            desired_retention: 0.8,
+            ..Fsrs::default()
+        };
+        assert!(relaxed.next_interval(13.8206) > 14);
+    }
+
+    #[test]
+    fn recall_and_lapse_update_the_memory_state() {
+        let fsrs = Fsrs::default();
+        let state = fsrs.next_state(None, 0.0, Rating::Good);
+
+        let recalled = fsrs.next_state(Some(state), 4.0, Rating::Good);
+        assert_close(recalled.stability, 14.8081);
+        assert_close(recalled.difficulty, 5.1618 - 0.031 * (5.1618 - 3.932));
+
+        let lapsed = fsrs.next_state(Some(state), 4.0, Rating::Again);
+        assert_close(lapsed.stability, 1.43323);
+        assert_close(lapsed.difficulty, 6.863031);
+    }
+
+    #[test]
+    fn review_schedules_by_stability() {
+        let fsrs = Fsrs::default();
+        let mut problem = LCProblem::new(42, "Trapping Rain Water", "Two Pointers", start());
+        fsrs.review(&mut problem, Rating::Good, start());
+        assert_eq!(problem.interval, 4);
+        assert_eq!(problem.next_due, start() + Duration::days(4));
+
+        fsrs.review(&mut problem, Rating::Good, start() + Duration::days(4));
+        assert_eq!(problem.interval, 15);
+        assert_eq!(problem.times_practiced, 2);
+    }
+
+    // A learner who remembers far longer than the default weights expect: every review
+    // is recalled, even months apart, except for one lapse in every fifth problem
+    fn strong_memory_histories() -> Vec<ReviewHistory> {
+        let gaps = [1, 10, 30, 90, 200];
+        (0..10)
+            .map(|problem| {
+                let mut day = 0;
+                let mut history = vec![(start(), Rating::Good)];
+                for (i, gap) in gaps.iter().enumerate() {
+                    day += gap;
+                    let rating = if problem % 5 == 0 && i == 2 {
+                        Rating::Again
+                    } else {
+                        Rating::Good
+                    };
+                    history.push((start() + Duration::days(day), rating));
+                }
+                history
+            })
+            .collect()
+    }
+
+    #[test]
+    fn optimizing_lowers_the_log_loss() {
+        let histories = strong_memory_histories();
+        let weights = optimize_weights(&histories).unwrap();
+        assert!(log_loss(&weights, &histories) < log_loss(&DEFAULT_WEIGHTS, &histories));
+        // longer remembered than expected, so initial stability after Good should grow
+        assert!(weights[2] > DEFAULT_WEIGHTS[2]);
+    }
+
+    #[test]
+    fn short_histories_keep_the_defaults() {
+        let histories: Vec<ReviewHistory> = strong_memory_histories().into_iter().take(5).collect();
+        assert_eq!(optimize_weights(&histories), None);
+    }
+
+    #[test]
+    fn saved_weights_are_reused_until_the_log_changes_enough() {
+        let store = MemoryStore::default();
+        let clock = FixedClock::new(start());
+        store
+            .add_problem(42, "Trapping Rain Water", "Two Pointers", None, &clock)
+            .unwrap();
+        let review = |count: usize| {
+            for _ in 0..count {
+                let problem = store.problem_by_number(42).unwrap().unwrap();
+                store
+                    .complete_problem(&problem, Rating::Good, None, None, &Fsrs::default(), &clock)
+                    .unwrap();
+                clock.advance(TimeDelta::days(1));
+            }
+        };
+        let fitted_at = || store.fsrs_weights().unwrap().map(|fitted| fitted.fitted_at);
+
+        review(MIN_REVIEWS_TO_OPTIMIZE - 1);
+        current_weights(&store, &clock).unwrap();
+        assert_eq!(fitted_at(), None);
+
+        review(1);
+        current_weights(&store, &clock).unwrap();
+        let first_fit = fitted_at().unwrap();
+        assert_eq!(store.fsrs_weights().unwrap().unwrap().review_count, 32);
+
+        review(7);
+        current_weights(&store, &clock).unwrap();
+        assert_eq!(fitted_at(), Some(first_fit));
+
+        review(1);
+        current_weights(&store, &clock).unwrap();
+        assert!(fitted_at().unwrap() > first_fit);
+        assert_eq!(store.fsrs_weights().unwrap().unwrap().review_count, 40);
+    }
+}
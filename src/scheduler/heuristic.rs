@@ -0,0 +1,78 @@
+use crate::{
+    calendar::Calendar,
+    db::models::{LCProblem, Rating},
+    scheduler::Scheduler,
+};
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+// The original weighting: sample a few problems with a per-day seed and rank them
+// by days since last practiced over times practiced.
+pub struct Heuristic {
+    pub sample_size: usize,
+    pub limit: usize,
+}
+
+impl Scheduler for Heuristic {
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+
+    fn todays_problems(
+        &self,
+        all_problems: &[LCProblem],
+        now: DateTime<Utc>,
+        calendar: &Calendar,
+    ) -> Vec<LCProblem> {
+        let problems = select_random_problems(all_problems, self.sample_size, now, calendar);
+
+        let mut problems_with_weights: Vec<(f64, LCProblem)> = vec![];
+        let mut unique_ids = HashSet::new();
+
+        for problem in problems {
+            if !unique_ids.contains(&problem.id) {
+                let d_last = now.signed_duration_since(problem.last_practiced).num_days() as f64;
+                let n_practiced = problem.times_practiced as f64;
+                let weight = d_last / (1.0 + n_practiced);
+
+                problems_with_weights.push((weight, problem.clone()));
+                unique_ids.insert(problem.id.clone());
+            }
+        }
+
+        problems_with_weights.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        problems_with_weights
+            .into_iter()
+            .take(self.limit)
+            .map(|(_weight, problem)| problem)
+            .collect()
+    }
+
+    fn review(&self, problem: &mut LCProblem, rating: Rating, now: DateTime<Utc>) {
+        problem.last_practiced = now;
+        problem.times_practiced += 1;
+        problem.next_due = now;
+        problem.last_rating = Some(rating);
+    }
+}
+
+fn select_random_problems(
+    all_problems: &[LCProblem],
+    limit: usize,
+    now: DateTime<Utc>,
+    calendar: &Calendar,
+) -> Vec<LCProblem> {
+    // the sample changes when the practice day does, not at UTC midnight
+    let seed_string = calendar.today(now).format("%Y-%m-%d").to_string();
+
+    let seed: [u8; 32] = Sha256::digest(seed_string.as_bytes()).into();
+    let mut rng = StdRng::from_seed(seed);
+
+    let mut problems = all_problems.to_vec();
+    problems.shuffle(&mut rng);
+
+    problems.into_iter().take(limit).collect()
+}
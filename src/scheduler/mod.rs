@@ -0,0 +1,73 @@
+pub mod fsrs;
+pub mod heuristic;
+pub mod prior;
+pub mod sm2;
+
+use crate::{
+    calendar::Calendar,
+    config::SchedulerConfig,
+    db::models::{LCProblem, Rating},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+pub trait Scheduler {
+    fn name(&self) -> &'static str;
+
+    // The problems that should be practiced today, in the order they should be practiced
+    fn todays_problems(
+        &self,
+        all_problems: &[LCProblem],
+        now: DateTime<Utc>,
+        calendar: &Calendar,
+    ) -> Vec<LCProblem>;
+
+    // Updates the problem's scheduling state after a review with the given rating
+    fn review(&self, problem: &mut LCProblem, rating: Rating, now: DateTime<Utc>);
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedulerKind {
+    Heuristic,
+    #[serde(alias = "sm-2")]
+    Sm2,
+    Fsrs,
+}
+
+// `fsrs_weights` are only used by FSRS, see fsrs::current_weights
+pub fn build(config: &SchedulerConfig, fsrs_weights: [f64; 17]) -> Box<dyn Scheduler> {
+    let scheduler: Box<dyn Scheduler> = match config.algorithm {
+        SchedulerKind::Heuristic => Box::new(heuristic::Heuristic {
+            sample_size: config.sample_size,
+            limit: config.daily_limit.unwrap_or(3),
+        }),
+        SchedulerKind::Sm2 => Box::new(sm2::Sm2),
+        SchedulerKind::Fsrs => Box::new(fsrs::Fsrs {
+            weights: fsrs_weights,
+            desired_retention: config.desired_retention,
+        }),
+    };
+    if config.difficulty_prior {
+        Box::new(prior::DifficultyPrior { inner: scheduler })
+    } else {
+        scheduler
+    }
+}
+
+// Problems due at any point before the current practice day ends
+fn due_problems(
+    all_problems: &[LCProblem],
+    now: DateTime<Utc>,
+    calendar: &Calendar,
+) -> Vec<LCProblem> {
+    let (_, end_of_today) = calendar.day_range(calendar.today(now));
+    let mut due: Vec<LCProblem> = all_problems
+        .iter()
+        .filter(|problem| problem.next_due < end_of_today)
+        .cloned()
+        .collect();
+
+    due.sort_by_key(|problem| problem.next_due);
+    due
+}
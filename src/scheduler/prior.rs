@@ -0,0 +1,51 @@
+use crate::{
+    calendar::Calendar,
+    db::models::{Difficulty, LCProblem, Rating},
+    scheduler::Scheduler,
+};
+use chrono::{DateTime, Duration, Utc};
+
+// Stretches or shrinks the interval a scheduler picks after a problem's first review by the
+// problem's difficulty: an Easy problem rated Good is less likely to be forgotten in a day
+// than a Hard one. Later reviews are left alone, by then the ratings speak for themselves.
+pub struct DifficultyPrior {
+    pub inner: Box<dyn Scheduler>,
+}
+
+fn interval_factor(difficulty: Difficulty) -> f64 {
+    match difficulty {
+        Difficulty::Easy => 1.5,
+        Difficulty::Medium => 1.0,
+        Difficulty::Hard => 0.6,
+    }
+}
+
+impl Scheduler for DifficultyPrior {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn todays_problems(
+        &self,
+        all_problems: &[LCProblem],
+        now: DateTime<Utc>,
+        calendar: &Calendar,
+    ) -> Vec<LCProblem> {
+        self.inner.todays_problems(all_problems, now, calendar)
+    }
+
+    fn review(&self, problem: &mut LCProblem, rating: Rating, now: DateTime<Utc>) {
+        let first_review = problem.times_practiced == 0;
+        self.inner.review(problem, rating, now);
+
+        // The heuristic doesn't use intervals, so there is nothing to scale
+        if !first_review || problem.interval == 0 {
+            return;
+        }
+        if let Some(difficulty) = problem.lc_difficulty {
+            problem.interval =
+                ((problem.interval as f64 * interval_factor(difficulty)).round() as u32).max(1);
+            problem.next_due = now + Duration::days(problem.interval as i64);
+        }
+    }
+}
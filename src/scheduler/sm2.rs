@@ -0,0 +1,76 @@
+use crate::{
+    calendar::Calendar,
+    db::models::{LCProblem, Rating},
+    scheduler::{due_problems, Scheduler},
+};
+use chrono::{DateTime, Duration, Utc};
+
+// SuperMemo 2, see https://super-memory.com/english/ol/sm2.htm
+pub const DEFAULT_EASE_FACTOR: f64 = 2.5;
+const MIN_EASE_FACTOR: f64 = 1.3;
+
+// Easy reviews get pushed out further than plain SM-2 would, similar to Anki's easy bonus
+const EASY_BONUS: f64 = 1.3;
+const EASY_FIRST_INTERVAL: u32 = 4;
+
+// Recall quality on SM-2's 0-5 scale. Anything below 3 counts as a failed recall.
+const PASSING_QUALITY: u8 = 3;
+
+pub struct Sm2;
+
+impl Scheduler for Sm2 {
+    fn name(&self) -> &'static str {
+        "sm2"
+    }
+
+    fn todays_problems(
+        &self,
+        all_problems: &[LCProblem],
+        now: DateTime<Utc>,
+        calendar: &Calendar,
+    ) -> Vec<LCProblem> {
+        due_problems(all_problems, now, calendar)
+    }
+
+    fn review(&self, problem: &mut LCProblem, rating: Rating, now: DateTime<Utc>) {
+        apply_review(problem, rating, now);
+    }
+}
+
+pub fn quality(rating: Rating) -> u8 {
+    match rating {
+        Rating::Again => 1,
+        Rating::Hard => 3,
+        Rating::Good => 4,
+        Rating::Easy => 5,
+    }
+}
+
+pub fn apply_review(problem: &mut LCProblem, rating: Rating, now: DateTime<Utc>) {
+    let quality = quality(rating);
+
+    if quality >= PASSING_QUALITY {
+        problem.interval = match problem.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (problem.interval as f64 * problem.ease_factor).round() as u32,
+        };
+        if rating == Rating::Easy {
+            problem.interval =
+                ((problem.interval as f64 * EASY_BONUS).round() as u32).max(EASY_FIRST_INTERVAL);
+        }
+        problem.repetitions += 1;
+    } else {
+        problem.repetitions = 0;
+        problem.interval = 1;
+    }
+
+    let q = (5 - quality) as f64;
+    problem.ease_factor =
+        (problem.ease_factor + (0.1 - q * (0.08 + q * 0.02))).max(MIN_EASE_FACTOR);
+
+    problem.last_practiced = now;
+    problem.times_practiced += 1;
+    problem.next_due = now + Duration::days(problem.interval as i64);
+    problem.last_rating = Some(rating);
+}
@@ -0,0 +1,232 @@
+use crate::db::models::{LCProblem, Rating};
+use chrono::{DateTime, TimeDelta, Utc};
+
+// Wall-clock timer that can be paused. Times come from the caller so a Clock can drive it.
+#[derive(Debug, Clone)]
+pub struct Stopwatch {
+    started_at: DateTime<Utc>,
+    paused_at: Option<DateTime<Utc>>,
+    paused_for: TimeDelta, // total time spent paused before paused_at
+}
+
+impl Stopwatch {
+    pub fn start(now: DateTime<Utc>) -> Self {
+        Self {
+            started_at: now,
+            paused_at: None,
+            paused_for: TimeDelta::zero(),
+        }
+    }
+
+    pub fn elapsed(&self, now: DateTime<Utc>) -> TimeDelta {
+        let until = self.paused_at.unwrap_or(now);
+        (until - self.started_at - self.paused_for).max(TimeDelta::zero())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn toggle_pause(&mut self, now: DateTime<Utc>) {
+        match self.paused_at.take() {
+            Some(paused_at) => self.paused_for += now - paused_at,
+            None => self.paused_at = Some(now),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionResult {
+    pub lc_number: u32,
+    pub problem_name: String,
+    pub rating: Rating,
+    pub seconds: u32,
+}
+
+// Walks through a fixed queue of problems one at a time, timing each attempt
+#[derive(Debug, Clone)]
+pub struct Session {
+    queue: Vec<LCProblem>,
+    index: usize,
+    stopwatch: Stopwatch,
+    pub results: Vec<SessionResult>,
+    pub skipped: usize,
+}
+
+impl Session {
+    // None when there is nothing to practice
+    pub fn start(queue: Vec<LCProblem>, now: DateTime<Utc>) -> Option<Self> {
+        if queue.is_empty() {
+            return None;
+        }
+        Some(Self {
+            queue,
+            index: 0,
+            stopwatch: Stopwatch::start(now),
+            results: vec![],
+            skipped: 0,
+        })
+    }
+
+    // The problem being worked on, None once the queue is done
+    pub fn current(&self) -> Option<&LCProblem> {
+        self.queue.get(self.index)
+    }
+
+    // 1-based position of the current problem and the queue length
+    pub fn position(&self) -> (usize, usize) {
+        ((self.index + 1).min(self.queue.len()), self.queue.len())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.index >= self.queue.len()
+    }
+
+    pub fn stopwatch(&self) -> &Stopwatch {
+        &self.stopwatch
+    }
+
+    pub fn toggle_pause(&mut self, now: DateTime<Utc>) {
+        self.stopwatch.toggle_pause(now);
+    }
+
+    // Time spent on the current problem, as logged when it's finished
+    pub fn elapsed_seconds(&self, now: DateTime<Utc>) -> u32 {
+        self.stopwatch.elapsed(now).num_seconds().max(0) as u32
+    }
+
+    // Seconds left of a limit on the current problem, negative once it's over
+    pub fn seconds_left(&self, limit_minutes: u32, now: DateTime<Utc>) -> i64 {
+        i64::from(limit_minutes) * 60 - i64::from(self.elapsed_seconds(now))
+    }
+
+    // Logs the attempt on the current problem and starts the timer for the next one.
+    // Returns the seconds spent.
+    pub fn finish(&mut self, rating: Rating, now: DateTime<Utc>) -> u32 {
+        let seconds = self.elapsed_seconds(now);
+        if let Some(problem) = self.current() {
+            self.results.push(SessionResult {
+                lc_number: problem.lc_number,
+                problem_name: problem.problem_name.clone(),
+                rating,
+                seconds,
+            });
+        }
+        self.advance(now);
+        seconds
+    }
+
+    pub fn skip(&mut self, now: DateTime<Utc>) {
+        if !self.is_done() {
+            self.skipped += 1;
+        }
+        self.advance(now);
+    }
+
+    pub fn total_seconds(&self) -> u32 {
+        self.results.iter().map(|result| result.seconds).sum()
+    }
+
+    fn advance(&mut self, now: DateTime<Utc>) {
+        self.index = (self.index + 1).min(self.queue.len());
+        self.stopwatch = Stopwatch::start(now);
+    }
+}
+
+// 75 -> "1:15", 3725 -> "1:02:05"
+pub fn format_duration(seconds: i64) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    let seconds = seconds.unsigned_abs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{sign}{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{sign}{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, FixedClock};
+    use chrono::TimeZone;
+
+    fn clock() -> FixedClock {
+        FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap())
+    }
+
+    fn queue(clock: &FixedClock) -> Vec<LCProblem> {
+        vec![
+            LCProblem::new(1, "Two Sum", "Arrays & Hashing", clock.now()),
+            LCProblem::new(42, "Trapping Rain Water", "Two Pointers", clock.now()),
+            LCProblem::new(2, "Add Two Numbers", "Linked List", clock.now()),
+        ]
+    }
+
+    #[test]
+    fn nothing_to_practice_starts_no_session() {
+        assert!(Session::start(vec![], clock().now()).is_none());
+    }
+
+    #[test]
+    fn paused_time_is_left_out_of_the_elapsed_seconds() {
+        let clock = clock();
+        let mut session = Session::start(queue(&clock), clock.now()).unwrap();
+
+        clock.advance(TimeDelta::seconds(90));
+        session.toggle_pause(clock.now());
+        assert!(session.stopwatch().is_paused());
+        clock.advance(TimeDelta::minutes(10));
+        assert_eq!(session.elapsed_seconds(clock.now()), 90);
+
+        session.toggle_pause(clock.now());
+        assert!(!session.stopwatch().is_paused());
+        clock.advance(TimeDelta::seconds(30));
+        assert_eq!(session.elapsed_seconds(clock.now()), 120);
+    }
+
+    #[test]
+    fn the_countdown_goes_negative_once_the_limit_is_over() {
+        let clock = clock();
+        let session = Session::start(queue(&clock), clock.now()).unwrap();
+
+        clock.advance(TimeDelta::seconds(75));
+        assert_eq!(session.seconds_left(2, clock.now()), 45);
+        clock.advance(TimeDelta::seconds(60));
+        assert_eq!(session.seconds_left(2, clock.now()), -15);
+    }
+
+    #[test]
+    fn each_problem_is_timed_from_when_the_last_one_ended() {
+        let clock = clock();
+        let mut session = Session::start(queue(&clock), clock.now()).unwrap();
+
+        clock.advance(TimeDelta::seconds(300));
+        assert_eq!(session.finish(Rating::Good, clock.now()), 300);
+        assert_eq!(session.position(), (2, 3));
+
+        clock.advance(TimeDelta::seconds(50));
+        session.skip(clock.now());
+        assert_eq!(session.current().unwrap().lc_number, 2);
+
+        clock.advance(TimeDelta::seconds(200));
+        assert_eq!(session.finish(Rating::Hard, clock.now()), 200);
+        assert!(session.is_done());
+        assert!(session.current().is_none());
+
+        let logged: Vec<_> = session
+            .results
+            .iter()
+            .map(|result| (result.lc_number, result.rating, result.seconds))
+            .collect();
+        assert_eq!(logged, vec![(1, Rating::Good, 300), (2, Rating::Hard, 200)]);
+        assert_eq!((session.total_seconds(), session.skipped), (500, 1));
+    }
+
+    #[test]
+    fn durations_show_hours_only_when_needed() {
+        assert_eq!(format_duration(75), "1:15");
+        assert_eq!(format_duration(3725), "1:02:05");
+        assert_eq!(format_duration(-15), "-0:15");
+    }
+}
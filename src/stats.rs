@@ -0,0 +1,320 @@
+use crate::{
+    calendar::Calendar,
+    db::models::{Difficulty, LCProblem, Review},
+};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+// Interval after which a problem counts as mastered, the usual "mature card" cutoff
+pub const MASTERED_INTERVAL_DAYS: u32 = 21;
+
+// Label for problems without a difficulty
+pub const UNKNOWN_DIFFICULTY: &str = "Unknown";
+
+pub struct CategoryStats {
+    pub category: String,
+    pub problems: usize,
+    pub mastered: usize,
+    pub overdue: usize,
+    pub reviews: u32,
+}
+
+impl CategoryStats {
+    fn new(category: &str) -> Self {
+        CategoryStats {
+            category: category.to_string(),
+            problems: 0,
+            mastered: 0,
+            overdue: 0,
+            reviews: 0,
+        }
+    }
+
+    // Share of the category's problems that are mastered, 0 for an empty category
+    pub fn mastery(&self) -> f64 {
+        if self.problems == 0 {
+            0.0
+        } else {
+            self.mastered as f64 / self.problems as f64
+        }
+    }
+}
+
+// One entry per configured category (empty ones included, since those are the neglected
+// topics), followed by any categories only found in the database
+pub fn category_stats(
+    problems: &[LCProblem],
+    categories: &[String],
+    calendar: &Calendar,
+    now: DateTime<Utc>,
+) -> Vec<CategoryStats> {
+    grouped_stats(
+        problems,
+        categories,
+        |problem| &problem.problem_type,
+        calendar,
+        now,
+    )
+}
+
+// The same numbers grouped by difficulty, Easy to Hard, with problems that have none
+// counted under "Unknown" at the end
+pub fn difficulty_stats(
+    problems: &[LCProblem],
+    calendar: &Calendar,
+    now: DateTime<Utc>,
+) -> Vec<CategoryStats> {
+    let difficulties: Vec<String> = Difficulty::ALL.iter().map(|d| d.to_string()).collect();
+    grouped_stats(
+        problems,
+        &difficulties,
+        |problem| {
+            problem
+                .lc_difficulty
+                .map_or(UNKNOWN_DIFFICULTY, |difficulty| difficulty.as_str())
+        },
+        calendar,
+        now,
+    )
+}
+
+fn grouped_stats(
+    problems: &[LCProblem],
+    groups: &[String],
+    group_of: impl Fn(&LCProblem) -> &str,
+    calendar: &Calendar,
+    now: DateTime<Utc>,
+) -> Vec<CategoryStats> {
+    let end_of_today = end_of_today(calendar, now);
+    let mut stats: Vec<CategoryStats> = groups.iter().map(|g| CategoryStats::new(g)).collect();
+
+    for problem in problems {
+        let group = group_of(problem);
+        let index = match stats.iter().position(|s| s.category == group) {
+            Some(index) => index,
+            None => {
+                stats.push(CategoryStats::new(group));
+                stats.len() - 1
+            }
+        };
+        let entry = &mut stats[index];
+        entry.problems += 1;
+        entry.reviews += problem.times_practiced;
+        if problem.interval >= MASTERED_INTERVAL_DAYS {
+            entry.mastered += 1;
+        }
+        if problem.next_due < end_of_today {
+            entry.overdue += 1;
+        }
+    }
+    stats
+}
+
+// The first practice day of the week that ends `weeks_ago` weeks before `today`
+pub fn week_start(today: NaiveDate, weeks_ago: usize) -> NaiveDate {
+    today - Days::new(7 * weeks_ago as u64 + 6)
+}
+
+// Half-open range of review timestamps covered by the last `weeks` weeks up to `today`
+pub fn weekly_review_range(
+    calendar: &Calendar,
+    today: NaiveDate,
+    weeks: usize,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    (
+        calendar.start_of_day(week_start(today, weeks.saturating_sub(1))),
+        calendar.day_range(today).1,
+    )
+}
+
+// Review counts for the last `weeks` weeks of practice days ending on `today`, oldest
+// week first
+pub fn reviews_per_week(
+    reviews: &[Review],
+    weeks: usize,
+    calendar: &Calendar,
+    today: NaiveDate,
+) -> Vec<u64> {
+    let mut counts = vec![0; weeks];
+    for review in reviews {
+        let days_ago = (today - calendar.day_of(review.reviewed_at)).num_days();
+        if days_ago < 0 {
+            continue;
+        }
+        let weeks_ago = days_ago as usize / 7;
+        if weeks_ago < weeks {
+            counts[weeks - 1 - weeks_ago] += 1;
+        }
+    }
+    counts
+}
+
+// Problems due before the current practice day ends, longest overdue first
+pub fn most_overdue<'a>(
+    problems: &'a [LCProblem],
+    calendar: &Calendar,
+    now: DateTime<Utc>,
+    n: usize,
+) -> Vec<&'a LCProblem> {
+    let end_of_today = end_of_today(calendar, now);
+    let mut overdue: Vec<&LCProblem> = problems
+        .iter()
+        .filter(|p| p.next_due < end_of_today)
+        .collect();
+    overdue.sort_by_key(|p| p.next_due);
+    overdue.truncate(n);
+    overdue
+}
+
+// Mean interval in days over problems that have been practiced at least once
+pub fn average_interval(problems: &[LCProblem]) -> Option<f64> {
+    let intervals: Vec<u32> = problems
+        .iter()
+        .filter(|p| p.times_practiced > 0)
+        .map(|p| p.interval)
+        .collect();
+    if intervals.is_empty() {
+        None
+    } else {
+        Some(intervals.iter().sum::<u32>() as f64 / intervals.len() as f64)
+    }
+}
+
+fn end_of_today(calendar: &Calendar, now: DateTime<Utc>) -> DateTime<Utc> {
+    calendar.day_range(calendar.today(now)).1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::Rating;
+    use chrono::{TimeDelta, TimeZone};
+    use chrono_tz::Tz;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    // Days start at 4am UTC
+    fn calendar() -> Calendar {
+        Calendar::new(Some(Tz::UTC), 4)
+    }
+
+    fn review(reviewed_at: DateTime<Utc>) -> Review {
+        Review {
+            id: reviewed_at.to_rfc3339(),
+            problem_id: "p".to_string(),
+            reviewed_at,
+            rating: Rating::Good,
+            time_spent: None,
+            notes: None,
+        }
+    }
+
+    fn problem(lc_number: u32, problem_type: &str, interval: u32) -> LCProblem {
+        LCProblem {
+            interval,
+            next_due: at(2024, 4, 1, 12),
+            ..LCProblem::new(lc_number, "Problem", problem_type, at(2024, 1, 1, 12))
+        }
+    }
+
+    #[test]
+    fn weeks_are_bucketed_by_practice_day() {
+        let today = date(2024, 3, 10);
+        let reviews: Vec<Review> = [
+            at(2024, 2, 18, 12), // more than three weeks back
+            at(2024, 2, 19, 12),
+            at(2024, 3, 4, 3), // still 2024-03-03, a week back
+            at(2024, 3, 4, 5),
+            at(2024, 3, 10, 12),
+            at(2024, 3, 11, 3), // still today
+            at(2024, 3, 11, 5), // tomorrow
+        ]
+        .into_iter()
+        .map(review)
+        .collect();
+
+        assert_eq!(
+            reviews_per_week(&reviews, 3, &calendar(), today),
+            vec![1, 1, 3]
+        );
+        assert_eq!(week_start(today, 0), date(2024, 3, 4));
+        assert_eq!(
+            weekly_review_range(&calendar(), today, 3),
+            (at(2024, 2, 19, 4), at(2024, 3, 11, 4))
+        );
+    }
+
+    #[test]
+    fn mastery_is_counted_per_category_including_empty_ones() {
+        let problems = vec![
+            problem(1, "Arrays & Hashing", MASTERED_INTERVAL_DAYS),
+            problem(2, "Arrays & Hashing", 5),
+            LCProblem {
+                lc_difficulty: Some(Difficulty::Hard),
+                ..problem(3, "Graphs", 30)
+            },
+        ];
+        let categories = vec!["Arrays & Hashing".to_string(), "Trees".to_string()];
+        let now = at(2024, 3, 10, 12);
+
+        let stats = category_stats(&problems, &categories, &calendar(), now);
+        let summary: Vec<_> = stats
+            .iter()
+            .map(|s| (s.category.as_str(), s.problems, s.mastered, s.mastery()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Arrays & Hashing", 2, 1, 0.5),
+                ("Trees", 0, 0, 0.0),
+                ("Graphs", 1, 1, 1.0),
+            ]
+        );
+
+        let by_difficulty: Vec<_> = difficulty_stats(&problems, &calendar(), now)
+            .iter()
+            .map(|s| (s.category.clone(), s.problems))
+            .collect();
+        assert_eq!(
+            by_difficulty,
+            vec![
+                ("Easy".to_string(), 0),
+                ("Medium".to_string(), 0),
+                ("Hard".to_string(), 1),
+                (UNKNOWN_DIFFICULTY.to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn anything_due_before_the_practice_day_ends_is_overdue_oldest_first() {
+        let now = at(2024, 3, 10, 12);
+        let due = |lc_number, next_due| LCProblem {
+            next_due,
+            ..problem(lc_number, "Arrays & Hashing", 1)
+        };
+        let problems = vec![
+            due(1, now),                // just reviewed with the heuristic scheduler
+            due(2, at(2024, 3, 11, 5)), // tomorrow
+            due(3, now - TimeDelta::days(2)),
+            due(4, at(2024, 3, 11, 3)), // still today
+        ];
+
+        let numbers = |n| -> Vec<u32> {
+            most_overdue(&problems, &calendar(), now, n)
+                .iter()
+                .map(|p| p.lc_number)
+                .collect()
+        };
+        assert_eq!(numbers(10), vec![3, 1, 4]);
+        assert_eq!(numbers(2), vec![3, 1]);
+
+        let stats = category_stats(&problems, &[], &calendar(), now);
+        assert_eq!(stats[0].overdue, 3);
+    }
+}
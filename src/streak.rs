@@ -0,0 +1,219 @@
+use crate::calendar::Calendar;
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Streaks {
+    pub current: u32, // practice days in the running streak, today included if practiced
+    pub longest: u32, // best streak ever, in practice days
+    pub freezes_left: u32, // freezes still available this month
+}
+
+// Walks every practice day (as the calendar counts them) from the first one to `today`. A day
+// without practice is covered by a freeze while the month still has some left, otherwise
+// the streak resets. Frozen days keep a streak alive but don't add to its length, and
+// today only breaks a streak once it's over.
+pub fn compute_streaks(
+    practiced_at: &[DateTime<Utc>],
+    calendar: &Calendar,
+    today: NaiveDate,
+    freezes_per_month: u32,
+) -> Streaks {
+    let days: BTreeSet<NaiveDate> = practiced_at
+        .iter()
+        .map(|timestamp| calendar.day_of(*timestamp))
+        .filter(|day| *day <= today)
+        .collect();
+
+    let mut streaks = Streaks::default();
+    let mut freezes_used: HashMap<(i32, u32), u32> = HashMap::new();
+
+    if let Some(&first) = days.first() {
+        let mut day = first;
+        while day <= today {
+            if days.contains(&day) {
+                streaks.current += 1;
+                streaks.longest = streaks.longest.max(streaks.current);
+            } else if day < today && streaks.current > 0 {
+                let used = freezes_used.entry((day.year(), day.month())).or_default();
+                if *used < freezes_per_month {
+                    *used += 1;
+                } else {
+                    streaks.current = 0;
+                }
+            }
+            day = day + Days::new(1);
+        }
+    }
+
+    let used_this_month = freezes_used
+        .get(&(today.year(), today.month()))
+        .copied()
+        .unwrap_or(0);
+    streaks.freezes_left = freezes_per_month.saturating_sub(used_this_month);
+    streaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::{Etc, Tz};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn utc() -> Calendar {
+        Calendar::new(Some(Tz::UTC), 0)
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    // Noon UTC on each of the given days of March 2024
+    fn march(days: &[u32]) -> Vec<DateTime<Utc>> {
+        days.iter().map(|d| at(2024, 3, *d, 12, 0)).collect()
+    }
+
+    #[test]
+    fn no_practice_means_no_streak() {
+        let streaks = compute_streaks(&[], &utc(), date(2024, 3, 10), 2);
+        assert_eq!(
+            streaks,
+            Streaks {
+                current: 0,
+                longest: 0,
+                freezes_left: 2
+            }
+        );
+    }
+
+    #[test]
+    fn consecutive_days_up_to_today() {
+        let streaks = compute_streaks(&march(&[6, 7, 8, 9, 10]), &utc(), date(2024, 3, 10), 0);
+        assert_eq!((streaks.current, streaks.longest), (5, 5));
+    }
+
+    #[test]
+    fn today_not_practiced_yet_keeps_streak() {
+        let streaks = compute_streaks(&march(&[7, 8, 9]), &utc(), date(2024, 3, 10), 0);
+        assert_eq!((streaks.current, streaks.longest), (3, 3));
+    }
+
+    #[test]
+    fn missed_day_breaks_streak_without_freezes() {
+        let streaks = compute_streaks(&march(&[5, 6, 7, 8]), &utc(), date(2024, 3, 10), 0);
+        assert_eq!((streaks.current, streaks.longest), (0, 4));
+    }
+
+    #[test]
+    fn freeze_covers_a_missed_day() {
+        let streaks = compute_streaks(&march(&[5, 6, 8, 9]), &utc(), date(2024, 3, 9), 1);
+        assert_eq!(
+            streaks,
+            Streaks {
+                current: 4,
+                longest: 4,
+                freezes_left: 0
+            }
+        );
+    }
+
+    #[test]
+    fn streak_breaks_once_freezes_run_out() {
+        let streaks = compute_streaks(&march(&[1, 3, 5, 7]), &utc(), date(2024, 3, 7), 2);
+        assert_eq!((streaks.current, streaks.longest), (1, 3));
+    }
+
+    #[test]
+    fn freezes_reset_each_month() {
+        let practiced = vec![
+            at(2024, 2, 27, 12, 0),
+            at(2024, 2, 29, 12, 0),
+            at(2024, 3, 2, 12, 0),
+        ];
+        let streaks = compute_streaks(&practiced, &utc(), date(2024, 3, 2), 1);
+        assert_eq!(
+            streaks,
+            Streaks {
+                current: 3,
+                longest: 3,
+                freezes_left: 0
+            }
+        );
+    }
+
+    #[test]
+    fn several_reviews_on_one_day_count_once() {
+        let practiced = vec![
+            at(2024, 3, 9, 8, 0),
+            at(2024, 3, 9, 20, 0),
+            at(2024, 3, 10, 9, 0),
+        ];
+        let streaks = compute_streaks(&practiced, &utc(), date(2024, 3, 10), 0);
+        assert_eq!(streaks.current, 2);
+    }
+
+    #[test]
+    fn midnight_belongs_to_the_new_day() {
+        let practiced = vec![at(2024, 3, 9, 23, 59), at(2024, 3, 10, 0, 0)];
+        let streaks = compute_streaks(&practiced, &utc(), date(2024, 3, 10), 0);
+        assert_eq!(streaks.current, 2);
+    }
+
+    #[test]
+    fn local_time_zone_decides_the_day() {
+        // 22:30 on the 9th and 23:30 on the 10th in UTC-1, but the 9th and 11th in UTC
+        let practiced = vec![at(2024, 3, 9, 23, 30), at(2024, 3, 11, 0, 30)];
+        // Etc zones have their sign flipped: GMT+1 is an hour behind UTC
+        let west = Calendar::new(Some(Etc::GMTPlus1), 0);
+
+        let local = compute_streaks(&practiced, &west, date(2024, 3, 10), 0);
+        assert_eq!((local.current, local.longest), (2, 2));
+
+        let in_utc = compute_streaks(&practiced, &utc(), date(2024, 3, 11), 0);
+        assert_eq!((in_utc.current, in_utc.longest), (1, 1));
+    }
+
+    #[test]
+    fn late_evening_east_of_utc_is_already_tomorrow() {
+        // 23:30 UTC on the 9th is 01:30 on the 10th in UTC+2
+        let practiced = vec![at(2024, 3, 9, 23, 30)];
+        let east = Calendar::new(Some(Etc::GMTMinus2), 0);
+
+        let streaks = compute_streaks(&practiced, &east, date(2024, 3, 10), 0);
+        assert_eq!(streaks.current, 1);
+        let streaks = compute_streaks(&practiced, &east, date(2024, 3, 12), 0);
+        assert_eq!((streaks.current, streaks.longest), (0, 1));
+    }
+
+    #[test]
+    fn practice_before_day_start_counts_for_the_previous_day() {
+        // with days starting at 4am, 01:30 on the 10th is still the 9th
+        let practiced = vec![at(2024, 3, 9, 19, 0), at(2024, 3, 10, 1, 30)];
+        let late = Calendar::new(Some(Tz::UTC), 4);
+
+        let streaks = compute_streaks(&practiced, &late, date(2024, 3, 9), 0);
+        assert_eq!(streaks.current, 1);
+        let streaks = compute_streaks(&practiced, &utc(), date(2024, 3, 10), 0);
+        assert_eq!(streaks.current, 2);
+    }
+
+    #[test]
+    fn day_start_applies_in_local_time() {
+        // 06:30 UTC is 01:30 on the 10th in New York (UTC-5), before the 4am day start
+        let practiced = vec![at(2024, 3, 9, 12, 0), at(2024, 3, 10, 6, 30)];
+        let new_york = Calendar::new(Some(chrono_tz::America::New_York), 4);
+
+        let streaks = compute_streaks(&practiced, &new_york, date(2024, 3, 9), 0);
+        assert_eq!((streaks.current, streaks.longest), (1, 1));
+    }
+
+    #[test]
+    fn practice_after_today_is_ignored() {
+        let streaks = compute_streaks(&march(&[9, 10, 11]), &utc(), date(2024, 3, 10), 0);
+        assert_eq!(streaks.current, 2);
+    }
+}
@@ -0,0 +1,54 @@
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use std::sync::OnceLock;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+const THEME: &str = "base16-ocean.dark";
+const TAB: &str = "    "; // tabs would be drawn as a single cell
+
+// Loading the syntax definitions takes a moment, so it happens once, on first use
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme() -> &'static Theme {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    &THEMES.get_or_init(ThemeSet::load_defaults).themes[THEME]
+}
+
+// The code as styled lines. `language` is a name or file extension; code in a language
+// syntect doesn't know is returned unstyled.
+pub fn highlight(code: &str, language: &str) -> Vec<Line<'static>> {
+    let plain = |line: &str| Line::from(line.trim_end_matches(['\r', '\n']).replace('\t', TAB));
+
+    let syntaxes = syntaxes();
+    let Some(syntax) = syntaxes.find_syntax_by_token(language) else {
+        return code.lines().map(plain).collect();
+    };
+    let mut highlighter = HighlightLines::new(syntax, theme());
+    LinesWithEndings::from(code)
+        .map(|line| match highlighter.highlight_line(line, syntaxes) {
+            Ok(ranges) => Line::from(
+                ranges
+                    .into_iter()
+                    .map(|(style, text)| {
+                        let color = style.foreground;
+                        Span::styled(
+                            text.trim_end_matches(['\r', '\n']).replace('\t', TAB),
+                            Style::default().fg(Color::Rgb(color.r, color.g, color.b)),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Err(_) => plain(line),
+        })
+        .collect()
+}
@@ -1,7 +1,9 @@
 // pub mod input;
+#[allow(clippy::module_inception)]
 pub mod tui;
 
+mod highlight;
 mod stateful_list;
 mod tabs;
+mod text_area;
 mod ui;
-mod validation;
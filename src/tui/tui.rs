@@ -1,38 +1,52 @@
 use crate::{
+    calendar::Calendar,
+    catalog::{search_by_name, search_by_number, CatalogEntry},
+    clock::Clock,
+    config::Config,
     db::{
-        db::{get_all_problems, insert_problem, problem_exists, update_problem_as_completed},
-        models::LCProblem,
+        db::Rows,
+        models::{Difficulty, LCProblem, Rating, Review, Solution},
+        store::ProblemStore,
     },
+    error::{Context, Error, Result},
+    exchange::{export::export, Format},
+    heatmap::Heatmap,
+    scaffold::create_scratch_file,
+    scheduler::Scheduler,
+    session::{format_duration, Session},
+    stats::{reviews_per_week, weekly_review_range},
+    streak::{compute_streaks, Streaks},
     tui::{
-        stateful_list::StatefulList,
-        tabs::TabsState,
-        ui,
-        validation::{number_validator, CATEGORIES},
+        highlight::highlight, stateful_list::StatefulList, tabs::TabsState, text_area::TextArea, ui,
     },
-    utils::get_todays_problems,
+    utils::{get_todays_problems, search_problems},
+    validation::{number_validator, type_validator},
 };
 use ratatui::{
     backend::CrosstermBackend,
     crossterm::{
-        event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     },
     prelude::Backend,
+    text::Line,
     widgets::{ScrollbarState, TableState},
     Terminal,
 };
-use rusqlite::Connection;
 use std::{
-    error::Error,
-    io,
+    collections::HashSet,
+    fmt::Display,
+    fs, io,
+    path::Path,
+    process::{Command, ExitStatus},
+    rc::Rc,
     time::{Duration, Instant},
 };
 use tui_input::{backend::crossterm::EventHandler, Input};
 
-use super::validation::type_validator;
-
 const ITEM_ROW_HEIGHT: usize = 2;
+pub const STATS_WEEKS: usize = 12; // weeks of history in the Stats tab activity chart
 
 #[derive(PartialEq)]
 pub enum AppMode {
@@ -40,12 +54,14 @@ pub enum AppMode {
     Input,
     Edit,
     Update,
+    Session,
 }
 
 #[derive(PartialEq)]
 pub enum AppView {
     Overview,
     Editor,
+    Stats,
 }
 
 #[derive(PartialEq)]
@@ -53,14 +69,7 @@ pub enum OverviewEditor {
     Number,
     Name,
     Type,
-}
-
-#[derive(PartialEq)]
-pub enum ErrorReason {
-    NoError,
-    ProblemExists,
-    CheckingProblemExists,
-    InsertionError,
+    Difficulty,
 }
 
 pub struct AppSettings {
@@ -72,83 +81,144 @@ pub struct AppSettings {
 pub struct App<'a> {
     pub title: &'a str,
     pub problems: Vec<LCProblem>,
+    pub listed_problems: Vec<LCProblem>, // the Editor rows, after the difficulty filter and search
     pub todays_problems: Vec<LCProblem>,
     pub should_quit: bool,
     pub tabs: TabsState<'a>,
     pub app_settings: AppSettings,
-    pub show_error_popup: bool,
-    pub error_reason: ErrorReason,
+    pub error: Option<String>, // shown in a popup until Enter or Esc
+    pub reported_skips: HashSet<String>, // unreadable rows already shown, so each is reported once
+    pub status: Option<String>, // one-off message shown under the tabs, cleared on the next key
+    pub show_rating_popup: bool,
+    pub show_delete_popup: bool,
+    pub ratings: StatefulList<Rating>,
     pub lc_number: Input,
     pub lc_name: Input,
-    pub categories: StatefulList<&'a str>,
-    pub db_connection: Connection,
+    pub categories: StatefulList<String>,
+    pub difficulties: StatefulList<Difficulty>, // nothing selected leaves the difficulty unknown
+    pub difficulty_filter: Option<Difficulty>,  // only problems of this difficulty are listed
+    pub search: Input,   // only problems whose name or notes match are listed
+    pub searching: bool, // typing into the search box
+    pub suggestions: Vec<&'static CatalogEntry>, // catalog matches for the LC Number/Name input
+    pub suggestion_index: usize,
+    pub store: Box<dyn ProblemStore>,
+    pub scheduler: Box<dyn Scheduler>,
+    pub config: Config,
+    pub calendar: Calendar, // time zone and day start the dates are shown in
+    pub clock: Rc<dyn Clock>,
     pub editor_state: TableState,
     pub editor_scroll_state: ScrollbarState,
     pub todays_problem_index: usize, // index of selected problem in Todays Problems (for updates)
+    pub reviews: Vec<Review>,        // every review, loaded with the stats for the notes search
+    pub reviews_today: usize,
+    pub weekly_reviews: Vec<u64>, // reviews per week over the last STATS_WEEKS, oldest first
+    pub heatmap: Heatmap,
+    pub streaks: Streaks,
+    pub session: Option<Session>, // practice session walking through Todays Problems
+    pub session_paused_for_rating: bool, // the rating popup paused the stopwatch, not the user
+    pub editor_history: Vec<Review>, // review log of the problem selected in the Editor table
+    pub editing_row: bool,        // inline edit of the selected Editor table row
+    pub edit_field: OverviewEditor,
+    pub edit_number: Input,
+    pub edit_name: Input,
+    pub edit_category: usize, // index into categories
+    pub edit_difficulty: Option<Difficulty>,
+    pub editing_notes: Option<String>, // id of the problem whose notes are open in notes_area
+    pub notes_area: TextArea,
+    pub show_code: bool, // read-only view of the selected problem's solutions
+    pub solutions: Vec<Solution>, // every saved version, oldest first
+    pub code_lines: Vec<Vec<Line<'static>>>, // the solutions' code, highlighted
+    pub code_version: usize, // index into solutions
+    pub code_scroll: u16,
+    pub compare_code: bool, // the previous version side by side with code_version
 }
 
 impl<'a> App<'a> {
-    pub fn new(title: &'a str, db_connection: Connection) -> Self {
-        let problems = match get_all_problems(&db_connection) {
-            Ok(problems) => problems,
-            Err(_) => vec![],
-        };
-        let problems_len = &problems.len();
-
-        let mut scroll_len = 0;
-        if *problems_len > 0 {
-            scroll_len = (problems_len - 1) * ITEM_ROW_HEIGHT;
-        }
-
-        let todays_problems = match get_todays_problems(&problems) {
-            Ok(todays_problems) => todays_problems,
-            Err(_) => vec![],
-        };
-
-        App {
+    pub fn new(
+        title: &'a str,
+        store: Box<dyn ProblemStore>,
+        scheduler: Box<dyn Scheduler>,
+        config: Config,
+        clock: Rc<dyn Clock>,
+    ) -> Self {
+        let calendar = Calendar::from(&config.time);
+        let today = calendar.today(clock.now());
+        let mut app = App {
             title,
-            problems,
-            todays_problems,
+            problems: vec![],
+            listed_problems: vec![],
+            todays_problems: vec![],
             should_quit: false,
-            tabs: TabsState::new(vec!["Overview", "Editor"]),
+            tabs: TabsState::new(vec!["Overview", "Editor", "Stats"]),
             app_settings: AppSettings {
                 mode: AppMode::Normal,
                 view: AppView::Overview,
                 editor: OverviewEditor::Number,
             },
-            show_error_popup: false,
-            error_reason: ErrorReason::NoError,
+            error: None,
+            reported_skips: HashSet::new(),
+            status: None,
+            show_rating_popup: false,
+            show_delete_popup: false,
+            ratings: StatefulList::with_items(Rating::ALL.to_vec()),
             lc_number: Input::default(),
             lc_name: Input::default(),
-            categories: StatefulList::with_items(CATEGORIES.to_vec()),
-            db_connection,
+            categories: StatefulList::with_items(config.categories.clone()),
+            difficulties: StatefulList::with_items(Difficulty::ALL.to_vec()),
+            difficulty_filter: None,
+            search: Input::default(),
+            searching: false,
+            suggestions: vec![],
+            suggestion_index: 0,
+            store,
+            scheduler,
+            config,
+            calendar,
+            clock,
             editor_state: TableState::default().with_selected(0),
-            editor_scroll_state: ScrollbarState::new(scroll_len),
+            editor_scroll_state: ScrollbarState::default(),
             todays_problem_index: 0,
-        }
+            reviews: vec![],
+            reviews_today: 0,
+            weekly_reviews: vec![0; STATS_WEEKS],
+            heatmap: Heatmap::new(&[], &calendar, today),
+            streaks: Streaks::default(),
+            session: None,
+            session_paused_for_rating: false,
+            editor_history: vec![],
+            editing_row: false,
+            edit_field: OverviewEditor::Number,
+            edit_number: Input::default(),
+            edit_name: Input::default(),
+            edit_category: 0,
+            edit_difficulty: None,
+            editing_notes: None,
+            notes_area: TextArea::new(""),
+            show_code: false,
+            solutions: vec![],
+            code_lines: vec![],
+            code_version: 0,
+            code_scroll: 0,
+            compare_code: false,
+        };
+        app.refresh_problems();
+        app
     }
 
-    pub fn start_ui(db_connection: Connection) -> Result<(), Box<dyn Error>> {
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen).expect("Failed to execute EnterAlternateScreen");
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-
-        let mut app = App::new("Rusty LCurve", db_connection);
-        let app_result = app.run_app(&mut terminal, Duration::from_millis(250));
+    pub fn start_ui(
+        store: Box<dyn ProblemStore>,
+        scheduler: Box<dyn Scheduler>,
+        config: Config,
+        clock: Rc<dyn Clock>,
+    ) -> Result<()> {
+        let mut terminal = setup_terminal().context("Could not set up the terminal")?;
 
-        disable_raw_mode()?;
-        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-        terminal.show_cursor()?;
-
-        if let Err(err) = app_result {
-            println!("{err:?}");
-        }
+        let tick_rate = Duration::from_millis(config.ui.tick_rate_ms);
+        let mut app = App::new("Rusty LCurve", store, scheduler, config, clock);
+        let app_result = app.run_app(&mut terminal, tick_rate);
 
-        terminal.clear()?;
-
-        Ok(())
+        restore_terminal(&mut terminal).context("Could not restore the terminal")?;
+        app_result.context("The terminal UI stopped")
     }
 
     fn run_app<B: Backend>(
@@ -156,20 +226,57 @@ impl<'a> App<'a> {
         terminal: &mut Terminal<B>,
         tick_rate: Duration,
     ) -> io::Result<()> {
-        let last_tick = Instant::now();
+        let mut last_tick = Instant::now();
         loop {
             terminal.draw(|frame| ui::draw(frame, self))?;
+            if last_tick.elapsed() >= tick_rate {
+                last_tick = Instant::now();
+            }
             let timeout = tick_rate.saturating_sub(last_tick.elapsed());
             if event::poll(timeout)? {
                 if let Event::Key(key) = event::read()? {
                     if key.kind == KeyEventKind::Press {
-                        if self.app_settings.mode == AppMode::Normal {
+                        self.status = None;
+                        if self.editing_notes.is_some() && self.error.is_some() {
+                            if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                                self.error = None;
+                            }
+                        } else if self.editing_notes.is_some() {
+                            match key.code {
+                                KeyCode::Char('s')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    self.save_notes()
+                                }
+                                KeyCode::Esc => self.editing_notes = None,
+                                _ => self.notes_area.handle_key(key),
+                            }
+                        } else if self.searching {
+                            match key.code {
+                                KeyCode::Enter => self.searching = false,
+                                KeyCode::Esc => self.clear_search(),
+                                _ => {
+                                    self.search.handle_event(&Event::Key(key));
+                                    self.editor_state.select(Some(0));
+                                    self.list_problems();
+                                }
+                            }
+                        } else if self.app_settings.mode == AppMode::Normal && self.error.is_some()
+                        {
+                            if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                                self.error = None;
+                            }
+                        } else if self.app_settings.mode == AppMode::Normal {
                             match key.code {
                                 KeyCode::Left | KeyCode::Char('h') => self.on_left(),
                                 KeyCode::Right | KeyCode::Char('l') => self.on_right(),
                                 KeyCode::Char('i') => self.app_settings.mode = AppMode::Input,
                                 KeyCode::Char('e') => self.app_settings.mode = AppMode::Edit,
                                 KeyCode::Char('u') => self.app_settings.mode = AppMode::Update,
+                                KeyCode::Char('s') => self.start_session(),
+                                KeyCode::Char('f') => self.cycle_difficulty_filter(),
+                                KeyCode::Char('/') => self.searching = true,
+                                KeyCode::Char('x') => self.export_database(),
                                 KeyCode::Char('q') => self.should_quit = true,
                                 _ => {}
                             }
@@ -179,11 +286,11 @@ impl<'a> App<'a> {
                                 KeyCode::Right => self.switch_editor_right(),
                                 KeyCode::Up => self.on_up(),
                                 KeyCode::Down => self.on_down(),
+                                KeyCode::Tab => self.accept_suggestion(),
                                 KeyCode::Esc => self.app_settings.mode = AppMode::Normal,
                                 KeyCode::Enter => {
-                                    if self.show_error_popup {
-                                        self.show_error_popup = false;
-                                        self.error_reason = ErrorReason::NoError;
+                                    if self.error.is_some() {
+                                        self.error = None;
                                     } else {
                                         self.on_enter();
                                     }
@@ -192,21 +299,140 @@ impl<'a> App<'a> {
                                     self.handle_input(key);
                                 }
                             }
+                        } else if self.app_settings.mode == AppMode::Edit && self.error.is_some() {
+                            if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                                self.error = None;
+                            }
+                        } else if self.app_settings.mode == AppMode::Edit && self.show_code {
+                            match key.code {
+                                KeyCode::Left => self.previous_code_version(),
+                                KeyCode::Right => self.next_code_version(),
+                                KeyCode::Up => {
+                                    self.code_scroll = self.code_scroll.saturating_sub(1)
+                                }
+                                KeyCode::Down => {
+                                    self.code_scroll = self.code_scroll.saturating_add(1)
+                                }
+                                KeyCode::PageUp => {
+                                    self.code_scroll = self.code_scroll.saturating_sub(20)
+                                }
+                                KeyCode::PageDown => {
+                                    self.code_scroll = self.code_scroll.saturating_add(20)
+                                }
+                                KeyCode::Char('v') => self.compare_code = !self.compare_code,
+                                KeyCode::Esc => self.show_code = false,
+                                _ => {}
+                            }
+                        } else if self.app_settings.mode == AppMode::Edit && self.show_delete_popup
+                        {
+                            match key.code {
+                                KeyCode::Char('y') | KeyCode::Enter => {
+                                    self.delete_selected_problem()
+                                }
+                                KeyCode::Char('n') | KeyCode::Esc => self.show_delete_popup = false,
+                                _ => {}
+                            }
+                        } else if self.app_settings.mode == AppMode::Edit && self.editing_row {
+                            match key.code {
+                                KeyCode::Left | KeyCode::BackTab => self.switch_edit_field_left(),
+                                KeyCode::Right | KeyCode::Tab => self.switch_edit_field_right(),
+                                KeyCode::Up => self.edit_choice_previous(),
+                                KeyCode::Down => self.edit_choice_next(),
+                                KeyCode::Enter => self.save_row_edit(),
+                                KeyCode::Esc => self.editing_row = false,
+                                _ => self.handle_edit_input(key),
+                            }
                         } else if self.app_settings.mode == AppMode::Edit {
                             match key.code {
                                 KeyCode::Up => self.previous_row(),
                                 KeyCode::Down => self.next_row(),
+                                KeyCode::Enter => self.start_row_edit(),
+                                KeyCode::Char('d') => self.open_delete_popup(),
+                                KeyCode::Char('c') => self.open_code(),
+                                KeyCode::Char('n') => {
+                                    self.open_notes(self.selected_problem().cloned())
+                                }
                                 KeyCode::Esc => self.app_settings.mode = AppMode::Normal,
                                 _ => {}
                             }
+                        } else if self.app_settings.mode == AppMode::Update && self.error.is_some()
+                        {
+                            if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                                self.error = None;
+                            }
+                        } else if self.app_settings.mode == AppMode::Update
+                            && self.show_rating_popup
+                        {
+                            match key.code {
+                                KeyCode::Up => self.ratings.previous(),
+                                KeyCode::Down => self.ratings.next(),
+                                KeyCode::Char(c @ '1'..='4') => {
+                                    self.ratings
+                                        .state
+                                        .select(c.to_digit(10).map(|d| d as usize - 1));
+                                    self.mark_problem_as_complete();
+                                }
+                                KeyCode::Enter => self.mark_problem_as_complete(),
+                                KeyCode::Esc => self.show_rating_popup = false,
+                                _ => {}
+                            }
                         } else if self.app_settings.mode == AppMode::Update {
                             match key.code {
                                 KeyCode::Up => self.todays_problems_index_up(),
                                 KeyCode::Down => self.todays_problems_index_down(),
-                                KeyCode::Enter => self.mark_problem_as_complete(),
+                                KeyCode::Enter => self.open_rating_popup(),
+                                KeyCode::Char('n') => self.open_notes(
+                                    self.todays_problems.get(self.todays_problem_index).cloned(),
+                                ),
+                                KeyCode::Char('o') => {
+                                    let problem = self
+                                        .todays_problems
+                                        .get(self.todays_problem_index)
+                                        .cloned();
+                                    self.open_in_editor(terminal, problem)?
+                                }
                                 KeyCode::Esc => self.app_settings.mode = AppMode::Normal,
                                 _ => {}
                             }
+                        } else if self.app_settings.mode == AppMode::Session && self.error.is_some()
+                        {
+                            if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                                self.error = None;
+                            }
+                        } else if self.app_settings.mode == AppMode::Session
+                            && self.show_rating_popup
+                        {
+                            match key.code {
+                                KeyCode::Up => self.ratings.previous(),
+                                KeyCode::Down => self.ratings.next(),
+                                KeyCode::Char(c @ '1'..='4') => {
+                                    self.ratings
+                                        .state
+                                        .select(c.to_digit(10).map(|d| d as usize - 1));
+                                    self.finish_session_problem();
+                                }
+                                KeyCode::Enter => self.finish_session_problem(),
+                                KeyCode::Esc => self.close_session_rating(),
+                                _ => {}
+                            }
+                        } else if self.app_settings.mode == AppMode::Session {
+                            match key.code {
+                                KeyCode::Char(' ') | KeyCode::Char('p') => {
+                                    self.toggle_session_pause()
+                                }
+                                KeyCode::Enter => self.open_session_rating(),
+                                KeyCode::Char('n') => self.skip_session_problem(),
+                                KeyCode::Char('o') => {
+                                    let problem = self
+                                        .session
+                                        .as_ref()
+                                        .and_then(|session| session.current())
+                                        .cloned();
+                                    self.open_in_editor(terminal, problem)?
+                                }
+                                KeyCode::Esc => self.end_session(),
+                                _ => {}
+                            }
                         }
                     }
                 }
@@ -219,39 +445,48 @@ impl<'a> App<'a> {
 
     fn on_left(&mut self) {
         self.tabs.previous();
-        if self.tabs.index == 0 {
-            self.app_settings.view = AppView::Overview;
-        } else {
-            self.app_settings.view = AppView::Editor;
-        }
+        self.sync_view();
     }
 
     fn on_right(&mut self) {
         self.tabs.next();
-        if self.tabs.index == 0 {
-            self.app_settings.view = AppView::Overview;
-        } else {
-            self.app_settings.view = AppView::Editor;
-        }
+        self.sync_view();
+    }
+
+    fn sync_view(&mut self) {
+        self.app_settings.view = match self.tabs.index {
+            0 => AppView::Overview,
+            1 => AppView::Editor,
+            _ => AppView::Stats,
+        };
     }
 
     fn on_up(&mut self) {
         if self.app_settings.editor == OverviewEditor::Type {
             self.categories.previous();
+        } else if self.app_settings.editor == OverviewEditor::Difficulty {
+            self.difficulties.previous();
+        } else if !self.suggestions.is_empty() {
+            let n_suggestions = self.suggestions.len();
+            self.suggestion_index = (self.suggestion_index + n_suggestions - 1) % n_suggestions;
         }
     }
 
     fn on_down(&mut self) {
         if self.app_settings.editor == OverviewEditor::Type {
             self.categories.next();
+        } else if self.app_settings.editor == OverviewEditor::Difficulty {
+            self.difficulties.next();
+        } else if !self.suggestions.is_empty() {
+            self.suggestion_index = (self.suggestion_index + 1) % self.suggestions.len();
         }
     }
 
     pub fn next_row(&mut self) {
-        if self.tabs.index == 1 {
+        if self.tabs.index == 1 && !self.listed_problems.is_empty() {
             let i = match self.editor_state.selected() {
                 Some(i) => {
-                    if i >= self.problems.len() - 1 {
+                    if i >= self.listed_problems.len() - 1 {
                         0
                     } else {
                         i + 1
@@ -261,15 +496,16 @@ impl<'a> App<'a> {
             };
             self.editor_state.select(Some(i));
             self.editor_scroll_state = self.editor_scroll_state.position(i * ITEM_ROW_HEIGHT);
+            self.refresh_editor_history();
         }
     }
 
     pub fn previous_row(&mut self) {
-        if self.tabs.index == 1 {
+        if self.tabs.index == 1 && !self.listed_problems.is_empty() {
             let i = match self.editor_state.selected() {
                 Some(i) => {
                     if i == 0 {
-                        self.problems.len() - 1
+                        self.listed_problems.len() - 1
                     } else {
                         i - 1
                     }
@@ -278,58 +514,71 @@ impl<'a> App<'a> {
             };
             self.editor_state.select(Some(i));
             self.editor_scroll_state = self.editor_scroll_state.position(i * ITEM_ROW_HEIGHT);
+            self.refresh_editor_history();
         }
     }
 
     fn on_enter(&mut self) {
-        let number_valid = number_validator(&self.lc_number);
+        let number_valid = number_validator(self.lc_number.value());
         let name_valid = !self.lc_name.value().is_empty();
         let category_valid = if let Some(selected_index) = self.categories.state.selected() {
-            type_validator(CATEGORIES[selected_index])
+            type_validator(
+                &self.categories.items[selected_index],
+                &self.config.categories,
+            )
         } else {
             false
         };
 
-        if !number_valid || !name_valid || !category_valid {
-            self.show_error_popup = !number_valid || !name_valid || !category_valid;
-            self.error_reason = ErrorReason::InsertionError;
+        let mut problems = vec![];
+        if !number_valid {
+            problems.push("LC Number must be a whole number.");
+        }
+        if !name_valid {
+            problems.push("LC Name can't be empty.");
+        }
+        if !category_valid {
+            problems.push("Select a category with Up/Down.");
+        }
+        if !problems.is_empty() {
+            self.show_error(Error::validation(problems.join("\n")));
             return;
         }
 
-        let lc_number = self
-            .lc_number
-            .value()
-            .parse::<u32>()
-            .expect("Invalid LC number");
+        let (Ok(lc_number), Some(category_index)) = (
+            self.lc_number.value().trim().parse::<u32>(),
+            self.categories.state.selected(),
+        ) else {
+            return;
+        };
         let problem_name = self.lc_name.value();
-        if !self.show_error_popup {
-            match problem_exists(&self.db_connection, lc_number, problem_name) {
-                Ok(true) => {
-                    self.show_error_popup = true;
-                    self.error_reason = ErrorReason::ProblemExists;
-                }
-                Ok(false) => {
-                    let problem_type = CATEGORIES[self.categories.state.selected().unwrap()];
-                    if let Err(_err) =
-                        insert_problem(&self.db_connection, lc_number, &problem_name, problem_type)
-                    {
-                        self.show_error_popup = true;
-                        self.error_reason = ErrorReason::InsertionError;
-                    } else {
-                        self.lc_number.reset();
-                        self.lc_name.reset();
-                        self.categories.state.select(None);
-                        self.problems = match get_all_problems(&self.db_connection) {
-                            Ok(problems) => problems,
-                            Err(_) => vec![],
-                        }
-                    }
-                }
-                Err(_err) => {
-                    self.show_error_popup = true;
-                    self.error_reason = ErrorReason::CheckingProblemExists;
+        match self.store.problem_exists(lc_number, problem_name) {
+            Ok(true) => self.show_error(already_exists(lc_number, problem_name)),
+            Ok(false) => {
+                let problem_type = &self.categories.items[category_index];
+                let difficulty = self
+                    .difficulties
+                    .state
+                    .selected()
+                    .map(|i| self.difficulties.items[i]);
+                if let Err(err) = self.store.add_problem(
+                    lc_number,
+                    problem_name,
+                    problem_type,
+                    difficulty,
+                    self.clock.as_ref(),
+                ) {
+                    self.show_error(err);
+                } else {
+                    self.lc_number.reset();
+                    self.lc_name.reset();
+                    self.categories.state.select(None);
+                    self.difficulties.state.select(None);
+                    self.suggestions.clear();
+                    self.refresh_problems();
                 }
             }
+            Err(err) => self.show_error(err),
         }
     }
 
@@ -337,6 +586,7 @@ impl<'a> App<'a> {
         match self.app_settings.editor {
             OverviewEditor::Name => self.app_settings.editor = OverviewEditor::Number,
             OverviewEditor::Type => self.app_settings.editor = OverviewEditor::Name,
+            OverviewEditor::Difficulty => self.app_settings.editor = OverviewEditor::Type,
             _ => {}
         }
     }
@@ -345,6 +595,7 @@ impl<'a> App<'a> {
         match self.app_settings.editor {
             OverviewEditor::Number => self.app_settings.editor = OverviewEditor::Name,
             OverviewEditor::Name => self.app_settings.editor = OverviewEditor::Type,
+            OverviewEditor::Type => self.app_settings.editor = OverviewEditor::Difficulty,
             _ => {}
         }
     }
@@ -353,44 +604,815 @@ impl<'a> App<'a> {
         match self.app_settings.editor {
             OverviewEditor::Number => {
                 self.lc_number.handle_event(&Event::Key(key));
+                self.suggestions = search_by_number(self.lc_number.value());
             }
             OverviewEditor::Name => {
                 self.lc_name.handle_event(&Event::Key(key));
+                self.suggestions = search_by_name(self.lc_name.value());
             }
             _ => {}
         }
+        self.suggestion_index = 0;
+    }
+
+    // Fills number, name, category and difficulty from the highlighted catalog suggestion
+    fn accept_suggestion(&mut self) {
+        let Some(entry) = self.suggestions.get(self.suggestion_index) else {
+            return;
+        };
+        self.lc_number = Input::new(entry.lc_number.to_string());
+        self.lc_name = Input::new(entry.problem_name.clone());
+        if let Some(index) = self
+            .categories
+            .items
+            .iter()
+            .position(|category| *category == entry.problem_type)
+        {
+            self.categories.state.select(Some(index));
+        }
+        self.difficulties.state.select(
+            self.difficulties
+                .items
+                .iter()
+                .position(|difficulty| *difficulty == entry.difficulty),
+        );
+        self.suggestions.clear();
     }
 
     fn todays_problems_index_up(&mut self) {
         if self.todays_problem_index > 0 {
             self.todays_problem_index -= 1;
         } else {
-            self.todays_problem_index = 2;
+            self.todays_problem_index = self.todays_problems.len().saturating_sub(1);
         }
     }
 
     fn todays_problems_index_down(&mut self) {
-        if self.todays_problem_index < 2 {
+        if self.todays_problem_index + 1 < self.todays_problems.len() {
             self.todays_problem_index += 1;
         } else {
             self.todays_problem_index = 0;
         }
     }
 
-    fn mark_problem_as_complete(&mut self) {
-        if let Some(problem) = get_todays_problems(&self.problems)
-            .unwrap()
+    fn open_rating_popup(&mut self) {
+        if self
+            .todays_problems
             .get(self.todays_problem_index)
+            .is_some()
         {
-            match update_problem_as_completed(&self.db_connection, &problem.id) {
-                Ok(_) => {
-                    self.problems = match get_all_problems(&self.db_connection) {
-                        Ok(problems) => problems,
-                        Err(_) => vec![],
-                    }
+            self.ratings.state.select(Some(2)); // default to Good
+            self.show_rating_popup = true;
+        }
+    }
+
+    fn selected_rating(&self) -> Option<Rating> {
+        self.ratings
+            .state
+            .selected()
+            .and_then(|i| self.ratings.items.get(i).copied())
+    }
+
+    // The problem the rating popup is asking about
+    pub fn rating_problem(&self) -> Option<&LCProblem> {
+        match &self.session {
+            Some(session) => session.current(),
+            None => self.todays_problems.get(self.todays_problem_index),
+        }
+    }
+
+    fn mark_problem_as_complete(&mut self) {
+        self.show_rating_popup = false;
+        let Some(rating) = self.selected_rating() else {
+            return;
+        };
+        if let Some(problem) = self.todays_problems.get(self.todays_problem_index) {
+            match self.store.complete_problem(
+                problem,
+                rating,
+                None,
+                None,
+                self.scheduler.as_ref(),
+                self.clock.as_ref(),
+            ) {
+                Ok(_) => self.refresh_problems(),
+                Err(err) => self.show_error(err),
+            }
+        }
+    }
+
+    fn start_session(&mut self) {
+        match Session::start(self.todays_problems.clone(), self.clock.now()) {
+            Some(session) => {
+                self.session = Some(session);
+                self.session_paused_for_rating = false;
+                self.app_settings.mode = AppMode::Session;
+            }
+            None => self.status = Some("Nothing due today, no session to start".to_string()),
+        }
+    }
+
+    // Countdown for the current session problem in minutes, from its difficulty if known
+    pub fn session_time_limit(&self) -> Option<u32> {
+        let problem = self.session.as_ref()?.current()?;
+        self.config.session.time_limit_for(problem.lc_difficulty)
+    }
+
+    // Writes the problem's scratch file and hands the terminal to the editor until it exits.
+    // Only failing to get the terminal back ends the UI; anything else shows the error popup.
+    fn open_in_editor<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        problem: Option<LCProblem>,
+    ) -> io::Result<()> {
+        let Some(problem) = problem else {
+            return Ok(());
+        };
+        let editor = &self.config.editor;
+        let path = match create_scratch_file(&editor.scratch_dir(), &problem, editor.language) {
+            Ok(path) => path,
+            Err(err) => {
+                self.show_error(err);
+                return Ok(());
+            }
+        };
+        let command = editor.command();
+
+        suspend_terminal(terminal)?;
+        let status = run_editor(&command, &path);
+        resume_terminal(terminal)?;
+
+        match status {
+            Ok(status) if status.success() => {
+                self.status = Some(format!("Edited {}", path.display()))
+            }
+            Ok(status) => self.show_error(format!("{command} exited with {status}")),
+            Err(err) => self.show_error(err),
+        }
+        Ok(())
+    }
+
+    fn toggle_session_pause(&mut self) {
+        let now = self.clock.now();
+        if let Some(session) = self.session.as_mut() {
+            session.toggle_pause(now);
+        }
+    }
+
+    // Stops the clock while the rating is picked, so the time recorded is the solve time
+    fn open_session_rating(&mut self) {
+        let now = self.clock.now();
+        if let Some(session) = self.session.as_mut() {
+            if !session.stopwatch().is_paused() {
+                session.toggle_pause(now);
+                self.session_paused_for_rating = true;
+            }
+            self.ratings.state.select(Some(2)); // default to Good
+            self.show_rating_popup = true;
+        }
+    }
+
+    // Resumes the stopwatch unless it was already paused when the rating popup opened
+    fn close_session_rating(&mut self) {
+        self.show_rating_popup = false;
+        if self.session_paused_for_rating {
+            self.session_paused_for_rating = false;
+            self.toggle_session_pause();
+        }
+    }
+
+    fn finish_session_problem(&mut self) {
+        self.show_rating_popup = false;
+        let now = self.clock.now();
+        let (Some(rating), Some(session)) = (self.selected_rating(), self.session.as_ref()) else {
+            return;
+        };
+        let Some(problem) = session.current().cloned() else {
+            return;
+        };
+        // finished on a copy, kept only once the review is saved
+        let mut session = session.clone();
+        let seconds = session.finish(rating, now);
+
+        if let Err(err) = self.store.complete_problem(
+            &problem,
+            rating,
+            Some(seconds),
+            None,
+            self.scheduler.as_ref(),
+            self.clock.as_ref(),
+        ) {
+            // stays paused on this problem so the attempt can be rated again
+            return self.show_error(err);
+        }
+        self.session = Some(session);
+        self.session_paused_for_rating = false;
+        self.refresh_problems();
+        if self.session.as_ref().is_some_and(Session::is_done) {
+            self.end_session();
+        }
+    }
+
+    fn skip_session_problem(&mut self) {
+        let now = self.clock.now();
+        self.session_paused_for_rating = false;
+        if let Some(session) = self.session.as_mut() {
+            session.skip(now);
+            if session.is_done() {
+                self.end_session();
+            }
+        }
+    }
+
+    fn end_session(&mut self) {
+        if let Some(session) = self.session.take() {
+            let mut summary = format!(
+                "Session over: {} problems in {}",
+                session.results.len(),
+                format_duration(session.total_seconds() as i64)
+            );
+            if session.skipped > 0 {
+                summary.push_str(&format!(", {} skipped", session.skipped));
+            }
+            self.status = Some(summary);
+        }
+        self.show_rating_popup = false;
+        self.app_settings.mode = AppMode::Normal;
+    }
+
+    // The problem selected in the Editor table
+    pub fn selected_problem(&self) -> Option<&LCProblem> {
+        self.editor_state
+            .selected()
+            .and_then(|i| self.listed_problems.get(i))
+    }
+
+    fn start_row_edit(&mut self) {
+        if self.tabs.index != 1 {
+            return;
+        }
+        if let Some(problem) = self.selected_problem().cloned() {
+            self.edit_number = Input::new(problem.lc_number.to_string());
+            self.edit_name = Input::new(problem.problem_name);
+            self.edit_category = self
+                .categories
+                .items
+                .iter()
+                .position(|category| *category == problem.problem_type)
+                .unwrap_or(0);
+            self.edit_difficulty = problem.lc_difficulty;
+            self.edit_field = OverviewEditor::Number;
+            self.editing_row = true;
+        }
+    }
+
+    fn switch_edit_field_left(&mut self) {
+        match self.edit_field {
+            OverviewEditor::Name => self.edit_field = OverviewEditor::Number,
+            OverviewEditor::Type => self.edit_field = OverviewEditor::Name,
+            OverviewEditor::Difficulty => self.edit_field = OverviewEditor::Type,
+            _ => {}
+        }
+    }
+
+    fn switch_edit_field_right(&mut self) {
+        match self.edit_field {
+            OverviewEditor::Number => self.edit_field = OverviewEditor::Name,
+            OverviewEditor::Name => self.edit_field = OverviewEditor::Type,
+            OverviewEditor::Type => self.edit_field = OverviewEditor::Difficulty,
+            _ => {}
+        }
+    }
+
+    fn edit_choice_previous(&mut self) {
+        match self.edit_field {
+            OverviewEditor::Type => {
+                let n_categories = self.categories.items.len();
+                self.edit_category = (self.edit_category + n_categories - 1) % n_categories;
+            }
+            OverviewEditor::Difficulty => {
+                self.edit_difficulty = cycle_difficulty(self.edit_difficulty, false);
+            }
+            _ => {}
+        }
+    }
+
+    fn edit_choice_next(&mut self) {
+        match self.edit_field {
+            OverviewEditor::Type => {
+                self.edit_category = (self.edit_category + 1) % self.categories.items.len();
+            }
+            OverviewEditor::Difficulty => {
+                self.edit_difficulty = cycle_difficulty(self.edit_difficulty, true);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_edit_input(&mut self, key: KeyEvent) {
+        match self.edit_field {
+            OverviewEditor::Number => {
+                self.edit_number.handle_event(&Event::Key(key));
+            }
+            OverviewEditor::Name => {
+                self.edit_name.handle_event(&Event::Key(key));
+            }
+            _ => {}
+        }
+    }
+
+    fn save_row_edit(&mut self) {
+        let Some(problem_id) = self.selected_problem().map(|problem| problem.id.clone()) else {
+            return;
+        };
+
+        let problem_name = self.edit_name.value().trim();
+        let lc_number = match self.edit_number.value().trim().parse::<u32>() {
+            Ok(lc_number) if !problem_name.is_empty() => lc_number,
+            Ok(_) => return self.show_error(Error::validation("LC Name can't be empty.")),
+            Err(_) => {
+                return self.show_error(Error::validation("LC Number must be a whole number."))
+            }
+        };
+        let problem_type = self.categories.items[self.edit_category].clone();
+
+        match self
+            .store
+            .other_problem_exists(&problem_id, lc_number, problem_name)
+        {
+            Ok(true) => self.show_error(already_exists(lc_number, problem_name)),
+            Ok(false) => {
+                if let Err(err) = self.store.update_problem(
+                    &problem_id,
+                    lc_number,
+                    problem_name,
+                    &problem_type,
+                    self.edit_difficulty,
+                ) {
+                    self.show_error(err);
+                } else {
+                    self.editing_row = false;
+                    self.refresh_problems();
                 }
-                Err(_) => {}
+            }
+            Err(err) => self.show_error(err),
+        }
+    }
+
+    fn open_delete_popup(&mut self) {
+        if self.tabs.index == 1 && self.selected_problem().is_some() {
+            self.show_delete_popup = true;
+        }
+    }
+
+    fn delete_selected_problem(&mut self) {
+        self.show_delete_popup = false;
+        let Some(problem_id) = self.selected_problem().map(|problem| problem.id.clone()) else {
+            return;
+        };
+
+        if let Err(err) = self.store.delete_problem(&problem_id) {
+            return self.show_error(err);
+        }
+
+        self.refresh_problems();
+        if let Some(i) = self.editor_state.selected() {
+            let i = i.min(self.listed_problems.len().saturating_sub(1));
+            self.editor_state.select(Some(i));
+            self.editor_scroll_state = self.editor_scroll_state.position(i * ITEM_ROW_HEIGHT);
+            self.refresh_editor_history();
+        }
+    }
+
+    // Exports to a dated JSON file in an `exports` folder next to the database
+    fn export_database(&mut self) {
+        let Some(db_dir) = self
+            .store
+            .path()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+        else {
+            return self.show_error(Error::validation(
+                "Nothing to export, this session isn't saved to a database file.",
+            ));
+        };
+        let export_dir = db_dir.join("exports");
+        let export_path = export_dir.join(format!(
+            "rusty_lcurve-{}.json",
+            self.clock.now().format("%Y-%m-%d-%H%M%S")
+        ));
+
+        let result = fs::create_dir_all(&export_dir)
+            .context(format!("Could not create {}", export_dir.display()))
+            .and_then(|_| {
+                export(
+                    self.store.as_ref(),
+                    &export_path,
+                    Format::Json,
+                    self.clock.as_ref(),
+                )
+            });
+        match result {
+            Ok(summary) => {
+                self.status = Some(format!(
+                    "Exported {} problems and {} reviews to {}",
+                    summary.problems,
+                    summary.reviews,
+                    export_path.display()
+                ));
+                self.report_skipped(&summary.skipped);
+            }
+            Err(err) => self.show_error(err),
+        }
+    }
+
+    fn open_notes(&mut self, problem: Option<LCProblem>) {
+        if let Some(problem) = problem {
+            self.notes_area = TextArea::new(problem.notes.as_deref().unwrap_or_default());
+            self.editing_notes = Some(problem.id);
+        }
+    }
+
+    fn save_notes(&mut self) {
+        let Some(problem_id) = self.editing_notes.clone() else {
+            return;
+        };
+        let text = self.notes_area.text();
+        let notes = Some(text.trim()).filter(|notes| !notes.is_empty());
+        match self.store.update_notes(&problem_id, notes) {
+            Ok(()) => {
+                self.editing_notes = None;
+                self.status = Some("Notes saved".to_string());
+                self.refresh_problems();
+            }
+            Err(err) => self.show_error(err),
+        }
+    }
+
+    // Opens the newest solution of the problem selected in the Editor table
+    fn open_code(&mut self) {
+        let Some(problem) = self.selected_problem().cloned() else {
+            return;
+        };
+        let solutions = self.store.solutions_for_problem(&problem.id);
+        self.solutions = self.loaded(solutions);
+        if self.solutions.is_empty() {
+            self.status = Some(format!(
+                "No solutions saved for {} yet, add one with `rusty_lcurve solution`",
+                problem.lc_number
+            ));
+            return;
+        }
+        self.code_lines = self
+            .solutions
+            .iter()
+            .map(|solution| highlight(&solution.code, &solution.language))
+            .collect();
+        self.code_version = self.solutions.len() - 1;
+        self.code_scroll = 0;
+        self.show_code = true;
+    }
+
+    fn previous_code_version(&mut self) {
+        self.code_version = self.code_version.saturating_sub(1);
+    }
+
+    fn next_code_version(&mut self) {
+        if self.code_version + 1 < self.solutions.len() {
+            self.code_version += 1;
+        }
+    }
+
+    fn clear_search(&mut self) {
+        self.search.reset();
+        self.searching = false;
+        self.list_problems();
+    }
+
+    fn cycle_difficulty_filter(&mut self) {
+        self.difficulty_filter = cycle_difficulty(self.difficulty_filter, true);
+        self.status = Some(match self.difficulty_filter {
+            Some(difficulty) => format!("Showing {difficulty} problems only"),
+            None => "Showing problems of every difficulty".to_string(),
+        });
+        self.editor_state.select(Some(0));
+        self.list_problems();
+    }
+
+    fn refresh_problems(&mut self) {
+        let problems = self.store.problems();
+        self.problems = self.loaded(problems);
+        self.todays_problems = get_todays_problems(
+            &self.problems,
+            self.scheduler.as_ref(),
+            &self.calendar,
+            self.config.scheduler.daily_limit,
+            self.clock.as_ref(),
+        );
+        if self.todays_problem_index >= self.todays_problems.len() {
+            self.todays_problem_index = self.todays_problems.len().saturating_sub(1);
+        }
+        self.refresh_reviews();
+        self.list_problems();
+    }
+
+    // Fills the Editor rows from the loaded problems, leaving those used for scheduling alone
+    fn list_problems(&mut self) {
+        self.listed_problems = match self.difficulty_filter {
+            Some(difficulty) => self
+                .problems
+                .iter()
+                .filter(|problem| problem.lc_difficulty == Some(difficulty))
+                .cloned()
+                .collect(),
+            None => self.problems.clone(),
+        };
+        if !self.search.value().trim().is_empty() {
+            self.listed_problems =
+                search_problems(&self.listed_problems, &self.reviews, self.search.value());
+        }
+        self.editor_scroll_state = self
+            .editor_scroll_state
+            .content_length(self.listed_problems.len().saturating_sub(1) * ITEM_ROW_HEIGHT);
+        self.refresh_editor_history();
+    }
+
+    fn refresh_reviews(&mut self) {
+        let now = self.clock.now();
+        let today = self.calendar.today(now);
+        let (start_of_day, end_of_day) = self.calendar.day_range(today);
+        let reviews_today = self.store.reviews_between(start_of_day, end_of_day);
+        self.reviews_today = self.loaded(reviews_today).len();
+
+        let (from, to) = weekly_review_range(&self.calendar, today, STATS_WEEKS);
+        let recent_reviews = self.store.reviews_between(from, to);
+        let recent_reviews = self.loaded(recent_reviews);
+        self.weekly_reviews = reviews_per_week(&recent_reviews, STATS_WEEKS, &self.calendar, today);
+
+        let (from, to) = Heatmap::review_range(&self.calendar, today);
+        let year_reviews = self.store.reviews_between(from, to);
+        let year_reviews = self.loaded(year_reviews);
+        self.heatmap = Heatmap::new(&year_reviews, &self.calendar, today);
+
+        let all_reviews = self.store.reviews();
+        self.reviews = self.loaded(all_reviews);
+        let practiced_at: Vec<_> = self
+            .reviews
+            .iter()
+            .map(|review| review.reviewed_at)
+            .collect();
+        self.streaks = compute_streaks(
+            &practiced_at,
+            &self.calendar,
+            today,
+            self.config.streaks.freezes_per_month,
+        );
+    }
+
+    fn refresh_editor_history(&mut self) {
+        let history = self
+            .selected_problem()
+            .map(|problem| self.store.reviews_for_problem(&problem.id));
+        self.editor_history = match history {
+            Some(history) => self.loaded(history),
+            None => vec![],
+        };
+    }
+
+    fn show_error(&mut self, err: impl Display) {
+        self.error = Some(err.to_string());
+    }
+
+    // The rows that could be read. A failed load or unreadable rows show the error popup.
+    fn loaded<T>(&mut self, rows: Result<Rows<T>>) -> Vec<T> {
+        match rows {
+            Ok(rows) => {
+                self.report_skipped(&rows.skipped);
+                rows.rows
+            }
+            Err(err) => {
+                self.show_error(err);
+                vec![]
             }
         }
     }
+
+    fn report_skipped(&mut self, skipped: &[Error]) {
+        let new: Vec<String> = skipped
+            .iter()
+            .map(|err| err.to_string())
+            .filter(|message| self.reported_skips.insert(message.clone()))
+            .collect();
+        if !new.is_empty() {
+            self.show_error(format!(
+                "Some rows couldn't be read and were skipped:\n{}",
+                new.join("\n")
+            ));
+        }
+    }
+}
+
+fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(stdout))
+}
+
+fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+    suspend_terminal(terminal)?;
+    terminal.clear()
+}
+
+// Gives the terminal back to the shell, or to a program run from the UI
+fn suspend_terminal<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    terminal.show_cursor()
+}
+
+fn resume_terminal<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    // the next draw has to repaint everything, not just what changed
+    terminal.clear()
+}
+
+// `command` may carry arguments, e.g. "code --wait"
+fn run_editor(command: &str, path: &Path) -> Result<ExitStatus> {
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .context(format!("Could not run {command}"))
+}
+
+// Steps through unknown, Easy, Medium and Hard, wrapping around
+fn cycle_difficulty(current: Option<Difficulty>, forward: bool) -> Option<Difficulty> {
+    let choices = [
+        None,
+        Some(Difficulty::Easy),
+        Some(Difficulty::Medium),
+        Some(Difficulty::Hard),
+    ];
+    let index = choices
+        .iter()
+        .position(|choice| *choice == current)
+        .unwrap_or(0);
+    let next = if forward {
+        index + 1
+    } else {
+        index + choices.len() - 1
+    };
+    choices[next % choices.len()]
+}
+
+fn already_exists(lc_number: u32, problem_name: &str) -> Error {
+    Error::validation(format!(
+        "Problem {lc_number} or \"{problem_name}\" is already in the database."
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clock::FixedClock, db::store::MemoryStore, scheduler::fsrs::DEFAULT_WEIGHTS};
+    use chrono::{TimeZone, Utc};
+
+    fn clock() -> Rc<FixedClock> {
+        Rc::new(FixedClock::new(
+            Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap(),
+        ))
+    }
+
+    fn app(clock: Rc<FixedClock>) -> App<'static> {
+        let store = MemoryStore::default();
+        for (n, name, difficulty) in [
+            (1, "Two Sum", Difficulty::Easy),
+            (42, "Trapping Rain Water", Difficulty::Hard),
+        ] {
+            store
+                .add_problem(
+                    n,
+                    name,
+                    "Arrays & Hashing",
+                    Some(difficulty),
+                    clock.as_ref(),
+                )
+                .unwrap();
+        }
+        let config = Config::default();
+        let scheduler = crate::scheduler::build(&config.scheduler, DEFAULT_WEIGHTS);
+        App::new("test", Box::new(store), scheduler, config, clock)
+    }
+
+    fn numbers(problems: &[LCProblem]) -> Vec<u32> {
+        problems.iter().map(|problem| problem.lc_number).collect()
+    }
+
+    #[test]
+    fn the_difficulty_filter_only_narrows_the_listed_problems() {
+        let mut app = app(clock());
+        app.cycle_difficulty_filter();
+        assert_eq!(app.difficulty_filter, Some(Difficulty::Easy));
+        assert_eq!(numbers(&app.listed_problems), vec![1]);
+        assert_eq!(app.problems.len(), 2);
+        assert_eq!(app.todays_problems.len(), 2);
+
+        // reloading from the store keeps scheduling over every problem
+        app.refresh_problems();
+        assert_eq!(numbers(&app.listed_problems), vec![1]);
+        assert_eq!(app.todays_problems.len(), 2);
+    }
+
+    #[test]
+    fn searching_only_narrows_the_listed_problems() {
+        let mut app = app(clock());
+        app.search = Input::from("WATER");
+        app.list_problems();
+        assert_eq!(numbers(&app.listed_problems), vec![42]);
+        assert_eq!(app.problems.len(), 2);
+        assert_eq!(app.todays_problems.len(), 2);
+
+        app.clear_search();
+        assert_eq!(numbers(&app.listed_problems), vec![1, 42]);
+    }
+
+    #[test]
+    fn searching_matches_the_notes_of_reviews_loaded_on_refresh() {
+        let mut app = app(clock());
+        let two_sum = app.problems[0].clone();
+        app.store
+            .complete_problem(
+                &two_sum,
+                Rating::Good,
+                None,
+                Some("hash map of complements"),
+                app.scheduler.as_ref(),
+                app.clock.as_ref(),
+            )
+            .unwrap();
+        // keystrokes search the reviews loaded by the last refresh rather than the store
+        app.search = Input::from("complements");
+        app.list_problems();
+        assert!(app.listed_problems.is_empty());
+
+        app.refresh_problems();
+        assert_eq!(numbers(&app.listed_problems), vec![1]);
+    }
+
+    #[test]
+    fn session_reviews_log_the_sessions_time() {
+        let clock = clock();
+        let mut app = app(clock.clone());
+        app.start_session();
+        assert!(app.app_settings.mode == AppMode::Session);
+
+        clock.advance(chrono::TimeDelta::seconds(200));
+        app.open_session_rating();
+        // picking the rating doesn't count towards the time
+        clock.advance(chrono::TimeDelta::seconds(30));
+        app.finish_session_problem();
+
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.results[0].seconds, 200);
+        let reviews = app.store.reviews().unwrap().rows;
+        assert_eq!(reviews[0].time_spent, Some(200));
+        assert_eq!(session.position(), (2, 2));
+    }
+
+    #[test]
+    fn cancelling_a_rating_leaves_a_users_pause_alone() {
+        let clock = clock();
+        let mut app = app(clock.clone());
+        app.start_session();
+
+        clock.advance(chrono::TimeDelta::seconds(100));
+        app.toggle_session_pause();
+        app.open_session_rating();
+        app.close_session_rating();
+        clock.advance(chrono::TimeDelta::seconds(600));
+
+        let session = app.session.as_ref().unwrap();
+        assert!(session.stopwatch().is_paused());
+        assert_eq!(session.elapsed_seconds(clock.now()), 100);
+    }
+
+    #[test]
+    fn cancelling_a_rating_resumes_the_stopwatch_it_paused() {
+        let clock = clock();
+        let mut app = app(clock.clone());
+        app.start_session();
+
+        clock.advance(chrono::TimeDelta::seconds(100));
+        app.open_session_rating();
+        clock.advance(chrono::TimeDelta::seconds(30));
+        app.close_session_rating();
+        clock.advance(chrono::TimeDelta::seconds(50));
+
+        let session = app.session.as_ref().unwrap();
+        assert!(!session.stopwatch().is_paused());
+        assert_eq!(session.elapsed_seconds(clock.now()), 150);
+    }
 }
@@ -1,15 +1,18 @@
 use crate::{
     db::models::LCProblem,
-    tui::tui::{App, AppView, ErrorReason, OverviewEditor},
-    utils::format_date,
+    session::format_duration,
+    stats::{
+        average_interval, category_stats, difficulty_stats, most_overdue, week_start, CategoryStats,
+    },
+    tui::tui::{App, AppMode, AppView, OverviewEditor, STATS_WEEKS},
 };
 use ratatui::{
     layout::{Constraint, Flex, Layout, Margin, Position, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{self, Line, Span, Text},
     widgets::{
-        Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Scrollbar,
-        ScrollbarOrientation, Table, Tabs, Wrap,
+        Bar, BarChart, BarGroup, Block, Borders, Cell, Clear, Gauge, List, ListItem, Paragraph,
+        Row, Scrollbar, ScrollbarOrientation, Table, Tabs, Wrap,
     },
     Frame,
 };
@@ -22,20 +25,76 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         .iter()
         .map(|t| text::Line::from(Span::styled(*t, Style::default().fg(Color::Green))))
         .collect::<Tabs>()
-        .block(Block::bordered().title(app.title))
+        .block(
+            Block::bordered()
+                .title(app.title)
+                .title(streak_title(app).right_aligned())
+                .title_bottom(app.status.clone().unwrap_or_default())
+                .title_bottom(search_title(app).right_aligned()),
+        )
         .highlight_style(Style::default().fg(Color::Yellow))
         .select(app.tabs.index);
     frame.render_widget(tabs, chunks[0]);
-    match app.tabs.index {
-        0 => draw_first_tab(frame, app, chunks[1]),
-        1 => draw_second_tab(frame, app, chunks[1]),
-        _ => {}
-    };
-    if app.show_error_popup {
-        draw_error_popup(frame, &app.error_reason, chunks[1]);
+    if app.app_settings.mode == AppMode::Session {
+        draw_session(frame, app, chunks[1]);
+    } else {
+        match app.tabs.index {
+            0 => draw_first_tab(frame, app, chunks[1]),
+            1 => draw_second_tab(frame, app, chunks[1]),
+            2 => draw_third_tab(frame, app, chunks[1]),
+            _ => {}
+        };
+    }
+    if app.show_code {
+        draw_code(frame, app, chunks[1]);
+    }
+    if let Some(error) = &app.error {
+        draw_error_popup(frame, error, chunks[1]);
+    }
+    if app.show_rating_popup {
+        draw_rating_popup(frame, app, chunks[1]);
+    }
+    if app.show_delete_popup {
+        draw_delete_popup(frame, app, chunks[1]);
+    }
+    if app.editing_notes.is_some() {
+        draw_notes_editor(frame, app, chunks[1]);
+    }
+}
+
+fn search_title(app: &App) -> Line<'static> {
+    if app.searching {
+        Line::from(format!(" Search: {}▏ ", app.search.value())).yellow()
+    } else if !app.search.value().trim().is_empty() {
+        Line::from(format!(
+            " Search: {} ({} found, / to change) ",
+            app.search.value(),
+            app.listed_problems.len()
+        ))
+    } else {
+        Line::from(" / to search ").dark_gray()
     }
 }
 
+fn streak_title(app: &App) -> Line<'static> {
+    let streaks = &app.streaks;
+    let freezes = match streaks.freezes_left {
+        1 => "1 freeze left".to_string(),
+        n => format!("{n} freezes left"),
+    };
+    Line::from(vec![
+        Span::styled(
+            format!(" Streak: {} days ", streaks.current),
+            Style::default().fg(if streaks.current > 0 {
+                Color::Yellow
+            } else {
+                Color::DarkGray
+            }),
+        ),
+        Span::raw(format!("(best {}, {}) ", streaks.longest, freezes)),
+    ])
+}
+
 fn draw_first_tab(frame: &mut Frame, app: &mut App, area: Rect) {
     let chunks =
         Layout::vertical([Constraint::Percentage(20), Constraint::Percentage(60)]).split(area);
@@ -44,17 +103,372 @@ fn draw_first_tab(frame: &mut Frame, app: &mut App, area: Rect) {
 }
 
 fn draw_second_tab(frame: &mut Frame, app: &mut App, area: Rect) {
-    let chunks = Layout::vertical([Constraint::Length(90)]).split(area);
+    let chunks =
+        Layout::vertical([Constraint::Percentage(70), Constraint::Percentage(30)]).split(area);
+
+    let bottom = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[1]);
 
     draw_editor_table(frame, app, chunks[0]);
     draw_scrollbar(frame, app, chunks[0]);
+    draw_review_history(frame, app, bottom[0]);
+    draw_notes(frame, app.selected_problem(), bottom[1]);
+}
+
+fn draw_third_tab(frame: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(10),
+        Constraint::Percentage(30),
+        Constraint::Min(0),
+    ])
+    .split(area);
+    let middle = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[2]);
+
+    draw_stats_summary(frame, app, chunks[0]);
+    draw_heatmap(frame, app, chunks[1]);
+    draw_weekly_reviews(frame, app, middle[0]);
+    draw_most_overdue(frame, app, middle[1]);
+    let bottom = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[3]);
+    let now = app.clock.now();
+    draw_mastery(
+        frame,
+        "Category",
+        category_stats(&app.problems, &app.config.categories, &app.calendar, now),
+        bottom[0],
+    );
+    draw_mastery(
+        frame,
+        "Difficulty",
+        difficulty_stats(&app.problems, &app.calendar, now),
+        bottom[1],
+    );
+}
+
+// One problem at a time with a stopwatch, the countdown if a limit is set, and the
+// attempts so far
+fn draw_session(frame: &mut Frame, app: &mut App, area: Rect) {
+    let Some(session) = &app.session else {
+        return;
+    };
+    let Some(problem) = session.current() else {
+        return;
+    };
+    let now = app.clock.now();
+    let elapsed = session.elapsed_seconds(now);
+    let paused = session.stopwatch().is_paused();
+    let (position, total) = session.position();
+
+    let chunks = Layout::vertical([
+        Constraint::Length(7),
+        Constraint::Length(3),
+        Constraint::Min(0),
+    ])
+    .split(area);
+
+    let difficulty = problem
+        .lc_difficulty
+        .map(|difficulty| format!(", {difficulty}"))
+        .unwrap_or_default();
+    let last_rating = problem
+        .last_rating
+        .map(|rating| format!(", last rated {}", rating.as_str()))
+        .unwrap_or_default();
+    let mut stopwatch = Line::from(Span::styled(
+        format_duration(elapsed.into()),
+        Style::default()
+            .fg(if paused {
+                Color::DarkGray
+            } else {
+                Color::Yellow
+            })
+            .add_modifier(Modifier::BOLD),
+    ));
+    if paused {
+        stopwatch.push_span(Span::styled(
+            " paused",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    let text = Text::from(vec![
+        Line::from(Span::styled(
+            format!("{}: {}", problem.lc_number, problem.problem_name),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!(
+            "{}{}, practiced {}x{}",
+            problem.problem_type, difficulty, problem.times_practiced, last_rating
+        )),
+        Line::from(""),
+        stopwatch,
+        Line::from(Span::styled(
+            "Space pause, Enter rate and continue, n skip, o open in editor, Esc end session",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ]);
+    let current = Paragraph::new(text)
+        .centered()
+        .block(Block::bordered().title(format!("Session: problem {position} of {total}")));
+    frame.render_widget(current, chunks[0]);
+
+    let countdown_block = Block::bordered().title("Time limit");
+    match app.session_time_limit() {
+        Some(minutes) => {
+            let left = session.seconds_left(minutes, now);
+            let (label, color) = if left >= 0 {
+                (
+                    format!("{} left of {minutes} min", format_duration(left)),
+                    Color::Green,
+                )
+            } else {
+                (
+                    format!("{} over {minutes} min", format_duration(-left)),
+                    Color::Red,
+                )
+            };
+            let gauge = Gauge::default()
+                .block(countdown_block)
+                .gauge_style(Style::default().fg(color))
+                .ratio((f64::from(elapsed) / (f64::from(minutes) * 60.0)).clamp(0.0, 1.0))
+                .label(label);
+            frame.render_widget(gauge, chunks[1]);
+        }
+        None => {
+            let hint = Paragraph::new("No limit, set one under [session] in the config")
+                .style(Style::default().fg(Color::DarkGray))
+                .block(countdown_block);
+            frame.render_widget(hint, chunks[1]);
+        }
+    }
+
+    let results: Vec<ListItem> = session
+        .results
+        .iter()
+        .map(|result| {
+            ListItem::new(format!(
+                "{:>5}  {:<40} {:<6} {:>8}",
+                result.lc_number,
+                result.problem_name,
+                result.rating.as_str(),
+                format_duration(result.seconds as i64)
+            ))
+        })
+        .collect();
+    let done = List::new(results).block(Block::bordered().title(format!(
+        "Done ({} total, {} skipped)",
+        format_duration(session.total_seconds() as i64),
+        session.skipped
+    )));
+    frame.render_widget(done, chunks[2]);
+}
+
+// One column per week, Sunday on top; the oldest weeks are dropped when the area is narrow
+fn draw_heatmap(frame: &mut Frame, app: &mut App, area: Rect) {
+    const LABEL_WIDTH: usize = 4;
+    const SHADES: [Color; 5] = [
+        Color::Indexed(237),
+        Color::Indexed(22),
+        Color::Indexed(28),
+        Color::Indexed(34),
+        Color::Indexed(40),
+    ];
+
+    let heatmap = &app.heatmap;
+    let inner_width = (area.width.saturating_sub(2) as usize).saturating_sub(LABEL_WIDTH);
+    let cell_width = if inner_width >= heatmap.weeks() * 2 {
+        2
+    } else {
+        1
+    };
+    let first_week = heatmap.weeks().saturating_sub(inner_width / cell_width);
+
+    let mut months = " ".repeat(LABEL_WIDTH);
+    let mut week = first_week;
+    while week < heatmap.weeks() {
+        match heatmap.month_label(week) {
+            Some(month) => {
+                months.push_str(&month);
+                week += month.len().div_ceil(cell_width);
+                months.push_str(
+                    &" ".repeat(month.len().div_ceil(cell_width) * cell_width - month.len()),
+                );
+            }
+            None => {
+                months.push_str(&" ".repeat(cell_width));
+                week += 1;
+            }
+        }
+    }
+
+    let mut lines = vec![Line::from(months)];
+    for (weekday, label) in ["", "Mon", "", "Wed", "", "Fri", ""].iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{label:<LABEL_WIDTH$}"))];
+        for week in first_week..heatmap.weeks() {
+            let cell = match heatmap.count(week, weekday) {
+                Some(count) => Span::styled(
+                    format!("{:<cell_width$}", "■"),
+                    Style::default().fg(SHADES[heatmap.level(count)]),
+                ),
+                None => Span::raw(" ".repeat(cell_width)),
+            };
+            spans.push(cell);
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let title = format!(
+        "{} reviews on {} days, {} to {}",
+        heatmap.total(),
+        heatmap.active_days(),
+        heatmap.date(first_week, 0).format("%Y-%m-%d"),
+        heatmap.end.format("%Y-%m-%d")
+    );
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(title)),
+        area,
+    );
+}
+
+fn draw_stats_summary(frame: &mut Frame, app: &mut App, area: Rect) {
+    let practiced = app
+        .problems
+        .iter()
+        .filter(|problem| problem.times_practiced > 0)
+        .count();
+    let summary = format!(
+        "Problems: {}   Practiced: {}   Due today: {}   Reviews ({} weeks): {}   Avg interval: {}",
+        app.problems.len(),
+        practiced,
+        app.todays_problems.len(),
+        STATS_WEEKS,
+        app.weekly_reviews.iter().sum::<u64>(),
+        average_interval(&app.problems).map_or("-".to_string(), |days| format!("{days:.1} days"))
+    );
+
+    frame.render_widget(
+        Paragraph::new(summary).block(Block::bordered().title("Summary")),
+        area,
+    );
+}
+
+fn draw_weekly_reviews(frame: &mut Frame, app: &mut App, area: Rect) {
+    let today = app.calendar.today(app.clock.now());
+    let bar_width = (area.width.saturating_sub(2) / STATS_WEEKS as u16)
+        .saturating_sub(1)
+        .clamp(1, 6);
+    // narrow bars only have room for the day of the month
+    let label_format = if bar_width >= 5 { "%m-%d" } else { "%d" };
+    let bars: Vec<Bar> = app
+        .weekly_reviews
+        .iter()
+        .enumerate()
+        .map(|(i, count)| {
+            // the bar covers the 7 days ending (STATS_WEEKS - 1 - i) weeks ago
+            let week_start = week_start(today, STATS_WEEKS - 1 - i);
+            Bar::default()
+                .value(*count)
+                .label(Line::from(week_start.format(label_format).to_string()))
+        })
+        .collect();
+
+    let chart = BarChart::default()
+        .block(Block::bordered().title("Reviews per week"))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Green))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Green));
+
+    frame.render_widget(chart, area);
+}
+
+fn draw_most_overdue(frame: &mut Frame, app: &mut App, area: Rect) {
+    let now = app.clock.now();
+    let today = app.calendar.today(now);
+    let items: Vec<ListItem> = most_overdue(
+        &app.problems,
+        &app.calendar,
+        now,
+        area.height.saturating_sub(2) as usize,
+    )
+    .into_iter()
+    .map(|problem| {
+        let days = (today - app.calendar.day_of(problem.next_due)).num_days();
+        ListItem::new(Line::from(vec![
+            Span::styled(
+                format!("{:>3}d ", days),
+                Style::default().fg(if days > 0 { Color::Red } else { Color::Yellow }),
+            ),
+            Span::raw(format!("{}: {}", problem.lc_number, problem.problem_name)),
+        ]))
+    })
+    .collect();
+
+    frame.render_widget(
+        List::new(items).block(Block::bordered().title("Most overdue")),
+        area,
+    );
+}
+
+// Mastery per category or per difficulty, `group` names which
+fn draw_mastery(frame: &mut Frame, group: &str, stats: Vec<CategoryStats>, area: Rect) {
+    const BAR_WIDTH: usize = 20;
+
+    let rows: Vec<Row> = stats
+        .into_iter()
+        .map(|stats| {
+            let filled = (stats.mastery() * BAR_WIDTH as f64).round() as usize;
+            let bar = format!(
+                "{}{} {:>3.0}%",
+                "█".repeat(filled),
+                "░".repeat(BAR_WIDTH - filled),
+                stats.mastery() * 100.0
+            );
+            let row = Row::new(vec![
+                Cell::from(stats.category),
+                Cell::from(stats.problems.to_string()),
+                Cell::from(stats.reviews.to_string()),
+                Cell::from(bar).style(Style::default().fg(Color::Green)),
+                Cell::from(stats.overdue.to_string()).style(if stats.overdue > 0 {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                }),
+            ]);
+            // untouched topics stand out by being dimmed
+            if stats.problems == 0 {
+                row.style(Style::default().fg(Color::DarkGray))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Max(26),
+        Constraint::Length(9),
+        Constraint::Length(8),
+        Constraint::Length(BAR_WIDTH as u16 + 6),
+        Constraint::Length(8),
+    ];
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(vec![group, "Problems", "Reviews", "Mastered", "Overdue"])
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .block(Block::bordered().title(format!("{group} mastery")));
+
+    frame.render_widget(table, area);
 }
 
 fn draw_inputs(frame: &mut Frame, app: &mut App, area: Rect) {
     let chunks = Layout::horizontal([
         Constraint::Percentage(20),
-        Constraint::Percentage(50),
-        Constraint::Percentage(30),
+        Constraint::Percentage(40),
+        Constraint::Percentage(28),
+        Constraint::Percentage(12),
     ])
     .split(area);
 
@@ -68,8 +482,24 @@ fn draw_inputs(frame: &mut Frame, app: &mut App, area: Rect) {
             },
         );
 
+    let suggestion = match app.suggestions.get(app.suggestion_index) {
+        Some(entry) => format!(
+            "Tab: {} {} ({}, {}) {}/{}",
+            entry.lc_number,
+            entry.problem_name,
+            entry.problem_type,
+            entry.difficulty,
+            app.suggestion_index + 1,
+            app.suggestions.len()
+        ),
+        None => String::new(),
+    };
     let lc_name_paragraph = Paragraph::new(app.lc_name.value())
-        .block(Block::bordered().title("LC Name"))
+        .block(
+            Block::bordered()
+                .title("LC Name")
+                .title_bottom(Line::from(suggestion).dark_gray()),
+        )
         .style(if matches!(app.app_settings.editor, OverviewEditor::Name) {
             Style::default().fg(Color::Yellow)
         } else {
@@ -80,7 +510,7 @@ fn draw_inputs(frame: &mut Frame, app: &mut App, area: Rect) {
         .categories
         .items
         .iter()
-        .map(|i| ListItem::new(vec![text::Line::from(Span::raw(*i))]))
+        .map(|i| ListItem::new(vec![text::Line::from(Span::raw(i.as_str()))]))
         .collect();
     let lc_type_list = List::new(lc_categories)
         .block(Block::bordered().title("Categories"))
@@ -92,9 +522,28 @@ fn draw_inputs(frame: &mut Frame, app: &mut App, area: Rect) {
             Style::default()
         });
 
+    let difficulties: Vec<ListItem> = app
+        .difficulties
+        .items
+        .iter()
+        .map(|difficulty| ListItem::new(difficulty.as_str()))
+        .collect();
+    let difficulty_list = List::new(difficulties)
+        .block(Block::bordered().title("Difficulty"))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol(">")
+        .style(
+            if matches!(app.app_settings.editor, OverviewEditor::Difficulty) {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            },
+        );
+
     frame.render_widget(lc_number_paragraph, chunks[0]);
     frame.render_widget(lc_name_paragraph, chunks[1]);
     frame.render_stateful_widget(lc_type_list, chunks[2], &mut app.categories.state);
+    frame.render_stateful_widget(difficulty_list, chunks[3], &mut app.difficulties.state);
 
     if app.app_settings.view == AppView::Editor {
         match app.app_settings.editor {
@@ -116,44 +565,165 @@ fn draw_inputs(frame: &mut Frame, app: &mut App, area: Rect) {
 }
 
 fn draw_lists(frame: &mut Frame, app: &mut App, area: Rect) {
-    let chunks =
-        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).split(area);
+    let chunks = Layout::horizontal([
+        Constraint::Percentage(30),
+        Constraint::Percentage(30),
+        Constraint::Percentage(40),
+    ])
+    .split(area);
 
+    let todays_title = format!(
+        "Todays Problems ({}, {} done)",
+        app.scheduler.name(),
+        app.reviews_today
+    );
     let todays_problems_list = create_problem_lists(
-        "Todays Problems",
+        &todays_title,
         &app.todays_problems,
         true,
         Some(app.todays_problem_index),
     );
-    let problem_list = create_problem_lists("All Problems", &app.problems, false, None);
+    let all_title = match app.difficulty_filter {
+        Some(difficulty) => format!("All Problems ({difficulty} only, f to change)"),
+        None => "All Problems".to_string(),
+    };
+    let problem_list = create_problem_lists(&all_title, &app.listed_problems, false, None);
 
     frame.render_widget(todays_problems_list, chunks[0]);
-    frame.render_widget(problem_list, chunks[1]);
+    draw_notes(
+        frame,
+        app.todays_problems.get(app.todays_problem_index),
+        chunks[1],
+    );
+    frame.render_widget(problem_list, chunks[2]);
+}
+
+// The notes of the problem selected in Todays Problems or the Editor table
+fn draw_notes(frame: &mut Frame, problem: Option<&LCProblem>, area: Rect) {
+    let (title, notes) = match problem {
+        Some(problem) => (
+            format!("Notes: {}", problem.lc_number),
+            problem.notes.clone().unwrap_or_default(),
+        ),
+        None => ("Notes".to_string(), String::new()),
+    };
+    let paragraph = Paragraph::new(notes)
+        .block(
+            Block::bordered()
+                .title(title)
+                .title_bottom(Line::from("n to edit").dark_gray()),
+        )
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_notes_editor(frame: &mut Frame, app: &mut App, area: Rect) {
+    let popup_area = popup_area(area, 60, 50);
+    let title = app
+        .problems
+        .iter()
+        .chain(&app.todays_problems)
+        .find(|problem| app.editing_notes.as_ref() == Some(&problem.id))
+        .map_or("Notes".to_string(), |problem| {
+            format!("Notes: {}: {}", problem.lc_number, problem.problem_name)
+        });
+
+    // scrolled so the cursor line stays in view
+    let (row, col) = app.notes_area.cursor();
+    let height = popup_area.height.saturating_sub(2) as usize;
+    let scroll = (row + 1).saturating_sub(height);
+    let lines: Vec<Line> = app
+        .notes_area
+        .lines()
+        .iter()
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::bordered()
+                .title(title)
+                .title_bottom(Line::from("Ctrl+S save, Esc cancel").dark_gray()),
+        )
+        .style(Style::default().fg(Color::Yellow))
+        .scroll((scroll as u16, 0));
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+    frame.set_cursor_position(Position::new(
+        popup_area.x + col as u16 + 1,
+        popup_area.y + (row - scroll) as u16 + 1,
+    ));
 }
 
 fn draw_editor_table(frame: &mut Frame, app: &mut App, area: Rect) {
-    let problems = app.problems.clone();
+    let problems = app.listed_problems.clone();
 
     let headers = Row::new(vec![
         Cell::from("Number"),
         Cell::from("Name"),
         Cell::from("Type"),
+        Cell::from("Difficulty"),
         Cell::from("Start"),
         Cell::from("Last Practiced"),
         Cell::from("Times"),
+        Cell::from("Next Due"),
+        Cell::from("Last Rating"),
     ])
     .style(Style::default().fg(Color::Yellow));
 
+    let editing_index = if app.editing_row {
+        app.editor_state.selected()
+    } else {
+        None
+    };
+
     let rows: Vec<Row> = problems
         .iter()
-        .map(|problem| {
+        .enumerate()
+        .map(|(i, problem)| {
+            let (number, name, problem_type, difficulty) = if Some(i) == editing_index {
+                (
+                    edit_cell(
+                        app.edit_number.value(),
+                        app.edit_field == OverviewEditor::Number,
+                    ),
+                    edit_cell(
+                        app.edit_name.value(),
+                        app.edit_field == OverviewEditor::Name,
+                    ),
+                    edit_cell(
+                        &app.categories.items[app.edit_category],
+                        app.edit_field == OverviewEditor::Type,
+                    ),
+                    edit_cell(
+                        app.edit_difficulty
+                            .map_or("-", |difficulty| difficulty.as_str()),
+                        app.edit_field == OverviewEditor::Difficulty,
+                    ),
+                )
+            } else {
+                (
+                    Cell::from(problem.lc_number.to_string()),
+                    Cell::from(problem.problem_name.clone()),
+                    Cell::from(problem.problem_type.clone()),
+                    Cell::from(
+                        problem
+                            .lc_difficulty
+                            .map_or("-", |difficulty| difficulty.as_str()),
+                    ),
+                )
+            };
             Row::new(vec![
-                Cell::from(problem.lc_number.to_string()),
-                Cell::from(problem.problem_name.as_str()),
-                Cell::from(problem.problem_type.as_str()),
-                Cell::from(format_date(problem.start_date)),
-                Cell::from(format_date(problem.last_practiced)),
+                number,
+                name,
+                problem_type,
+                difficulty,
+                Cell::from(app.calendar.format_date(problem.start_date)),
+                Cell::from(app.calendar.format_date(problem.last_practiced)),
                 Cell::from(problem.times_practiced.to_string()),
+                Cell::from(app.calendar.format_date(problem.next_due)),
+                Cell::from(problem.last_rating.map_or("-", |rating| rating.as_str())),
             ])
         })
         .collect();
@@ -163,16 +733,22 @@ fn draw_editor_table(frame: &mut Frame, app: &mut App, area: Rect) {
         Constraint::Length(10),
         Constraint::Length(20),
         Constraint::Length(15),
+        Constraint::Length(10),
         Constraint::Length(20),
         Constraint::Length(20),
         Constraint::Length(10),
+        Constraint::Length(20),
+        Constraint::Length(12),
     ];
     let table = Table::new(rows, widths)
         .header(headers)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Leetcode Problems"),
+                .title(match app.difficulty_filter {
+                    Some(difficulty) => format!("Leetcode Problems ({difficulty} only)"),
+                    None => "Leetcode Problems".to_string(),
+                }),
         )
         .highlight_style(Style::default().fg(Color::Green))
         .highlight_symbol(Text::from(vec![
@@ -185,6 +761,91 @@ fn draw_editor_table(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_stateful_widget(table, area, &mut app.editor_state);
 }
 
+// The selected solution version, next to the one before it when comparing
+fn draw_code(frame: &mut Frame, app: &mut App, area: Rect) {
+    let title = app
+        .selected_problem()
+        .map(|problem| format!("{}: {}", problem.lc_number, problem.problem_name))
+        .unwrap_or_default();
+    let versions = if app.compare_code && app.code_version > 0 {
+        vec![app.code_version - 1, app.code_version]
+    } else {
+        vec![app.code_version]
+    };
+    let panes = Layout::horizontal(vec![Constraint::Fill(1); versions.len()]).split(area);
+
+    frame.render_widget(Clear, area);
+    for (pane, index) in panes.iter().zip(versions) {
+        let (Some(solution), Some(lines)) = (app.solutions.get(index), app.code_lines.get(index))
+        else {
+            continue;
+        };
+        let block = Block::bordered()
+            .title(format!(
+                "{title} v{} of {}, {}, saved {}",
+                solution.version,
+                app.solutions.len(),
+                solution.language,
+                app.calendar.format_date(solution.created_at)
+            ))
+            .title_bottom(
+                Line::from("Left/Right version, Up/Down scroll, v compare, Esc close").dark_gray(),
+            );
+        let code = Paragraph::new(lines.clone())
+            .block(block)
+            .scroll((app.code_scroll, 0));
+        frame.render_widget(code, *pane);
+    }
+}
+
+// A cell of the row being edited, with a trailing cursor on the active field
+fn edit_cell(value: &str, active: bool) -> Cell<'static> {
+    if active {
+        Cell::from(format!("{value}▏")).style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::UNDERLINED),
+        )
+    } else {
+        Cell::from(value.to_string()).style(Style::default().fg(Color::Yellow))
+    }
+}
+
+fn draw_review_history(frame: &mut Frame, app: &mut App, area: Rect) {
+    let rows: Vec<Row> = app
+        .editor_history
+        .iter()
+        .rev()
+        .map(|review| {
+            Row::new(vec![
+                Cell::from(app.calendar.format_date(review.reviewed_at)),
+                Cell::from(review.rating.as_str()),
+                Cell::from(
+                    review
+                        .time_spent
+                        .map_or("-".to_string(), |secs| format!("{}m", secs / 60)),
+                ),
+                Cell::from(review.notes.clone().unwrap_or_default()),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(12),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Min(0),
+    ];
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(vec!["Reviewed", "Rating", "Time", "Notes"])
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .block(Block::default().borders(Borders::ALL).title("History"));
+
+    frame.render_widget(table, area);
+}
+
 fn draw_scrollbar(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_stateful_widget(
         Scrollbar::default()
@@ -233,19 +894,14 @@ fn create_problem_lists<'a>(
     List::new(problem_items).block(Block::default().borders(Borders::ALL).title(title))
 }
 
-fn draw_error_popup(frame: &mut Frame, error_reason: &ErrorReason, area: Rect) {
+fn draw_error_popup(frame: &mut Frame, error: &str, area: Rect) {
     let popup_area = popup_area(area, 60, 40);
 
     let block = Block::bordered().title("Error").on_yellow();
 
-    let error_message = match error_reason {
-        ErrorReason::ProblemExists => "The problem already exists in the database. Please enter a unique problem.",
-        ErrorReason::CheckingProblemExists => "There was an error checking if the problem already exists. Please try again.",
-        ErrorReason::InsertionError => "1. Check your number input is numeric.\n2. Make sure you selected an input category.\nPress Enter to close the popup.",
-        ErrorReason::NoError => "",
-    };
-
-    let text = Text::from(error_message);
+    let mut text = Text::from(error.to_string());
+    text.push_line("");
+    text.push_line("Press Enter to close the popup.");
 
     let paragraph = Paragraph::new(text)
         .block(block)
@@ -256,6 +912,66 @@ fn draw_error_popup(frame: &mut Frame, error_reason: &ErrorReason, area: Rect) {
     frame.render_widget(paragraph, popup_area);
 }
 
+fn draw_rating_popup(frame: &mut Frame, app: &mut App, area: Rect) {
+    let popup_area = popup_area(area, 40, 30);
+
+    let title = match app.rating_problem() {
+        Some(problem) => format!("How did {} go?", problem.lc_number),
+        None => "How did it go?".to_string(),
+    };
+
+    let rating_items: Vec<ListItem> = app
+        .ratings
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, rating)| {
+            ListItem::new(Line::from(format!(
+                "{}. {} - {}",
+                i + 1,
+                rating.as_str(),
+                rating.description()
+            )))
+        })
+        .collect();
+
+    let rating_list = List::new(rating_items)
+        .block(Block::bordered().title(title))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">");
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(rating_list, popup_area, &mut app.ratings.state);
+}
+
+fn draw_delete_popup(frame: &mut Frame, app: &mut App, area: Rect) {
+    let popup_area = popup_area(area, 50, 20);
+
+    let message = match app
+        .editor_state
+        .selected()
+        .and_then(|i| app.listed_problems.get(i))
+    {
+        Some(problem) => format!(
+            "Delete {}: {} and its review history?\nPress y to delete or n to cancel.",
+            problem.lc_number, problem.problem_name
+        ),
+        None => String::new(),
+    };
+
+    let paragraph = Paragraph::new(Text::from(message))
+        .block(Block::bordered().title("Delete Problem").on_red())
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
 fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(Flex::Center);
     let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
@@ -1,6 +1,5 @@
-use tui_input::Input;
-
-pub const CATEGORIES: [&str; 18] = [
+// NeetCode's problem types, used unless the config file lists its own categories
+pub const DEFAULT_CATEGORIES: [&str; 18] = [
     "Arrays & Hashing",
     "Two Pointers",
     "Sliding Window",
@@ -21,10 +20,10 @@ pub const CATEGORIES: [&str; 18] = [
     "Bit Manipulation",
 ];
 
-pub fn number_validator(num: &Input) -> bool {
-    num.value().trim().parse::<u32>().is_ok()
+pub fn number_validator(num: &str) -> bool {
+    num.trim().parse::<u32>().is_ok()
 }
 
-pub fn type_validator(entered_type: &str) -> bool {
-    CATEGORIES.contains(&entered_type)
+pub fn type_validator(entered_type: &str, categories: &[String]) -> bool {
+    categories.iter().any(|category| category == entered_type)
 }